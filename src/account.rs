@@ -1,15 +1,24 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::{Deserialize, Serialize};
 use tracing::info;
 use uuid::Uuid;
 
 use crate::{
     coin::{Coin, CoinSymbol},
+    config::TradeMode,
     model::{
         account::{Account, AccountList, AccountType, Product, SingleAccount},
         event::CandleHistory,
         fee::FeeData,
-        order::OrderResponse,
+        order::{
+            CancelOrdersResponse, ConvertResponse, OrderList, OrderResponse, OrderSummary,
+            SingleOrder,
+        },
         TradeSide,
     },
     trading_bot::TradeSignal,
@@ -22,11 +31,19 @@ const ACCOUNT_API_URL: &str = "https://api.coinbase.com/api/v3/brokerage/account
 const PRODUCT_API_URL: &str = "https://api.coinbase.com/api/v3/brokerage/products";
 const ORDER_API_URL: &str = "https://api.coinbase.com/api/v3/brokerage/orders";
 const SUMMARY_API_URL: &str = "https://api.coinbase.com/api/v3/brokerage/transaction_summary";
+const ORDER_HISTORY_API_URL: &str = "https://api.coinbase.com/api/v3/brokerage/orders/historical";
+const CANCEL_API_URL: &str = "https://api.coinbase.com/api/v3/brokerage/orders/batch_cancel";
+const CONVERT_QUOTE_API_URL: &str = "https://api.coinbase.com/api/v3/brokerage/convert/quote";
+const CONVERT_TRADE_API_URL: &str = "https://api.coinbase.com/api/v3/brokerage/convert/trade";
 
 const PRODUCT_REQUEST_PATH: &str = "/api/v3/brokerage/products";
 const ACCOUNT_REQUEST_PATH: &str = "/api/v3/brokerage/accounts";
 const ORDER_REQUEST_PATH: &str = "/api/v3/brokerage/orders";
 const SUMMARY_REQUEST_PATH: &str = "/api/v3/brokerage/transaction_summary";
+const ORDER_HISTORY_REQUEST_PATH: &str = "/api/v3/brokerage/orders/historical";
+const CANCEL_REQUEST_PATH: &str = "/api/v3/brokerage/orders/batch_cancel";
+const CONVERT_QUOTE_REQUEST_PATH: &str = "/api/v3/brokerage/convert/quote";
+const CONVERT_TRADE_REQUEST_PATH: &str = "/api/v3/brokerage/convert/trade";
 
 const XRP_SELL_PLACES: i32 = 6;
 const XRP_BUY_PLACES: i32 = 4;
@@ -45,6 +62,77 @@ const LITE_BUY_PLACES: i32 = 2;
 
 const USDC_BUY_PLACES: i32 = 2;
 
+// Take profit is placed this many atr above the entry high.
+const TAKE_PROFIT_ATR_MODIFIER: f64 = 3.0;
+
+const ORDER_SETTLE_ATTEMPTS: usize = 10;
+const ORDER_SETTLE_WAIT: std::time::Duration = std::time::Duration::from_millis(500);
+
+// A failed close is tried again from the ticker after this, rather than on
+// every ticker.
+const CLOSE_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+// Sell side places for a defensive asset the bot has no precision for.
+const DEFENSIVE_SELL_PLACES: i32 = 6;
+
+// The coin threads and the pairs thread both write a coin's state file.
+static STATE_FILE_LOCK: Mutex<()> = Mutex::new(());
+
+// Direction of the open position, only meaningful while can_trade is false.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionSide {
+    #[default]
+    Long,
+    // Short a derivatives product, the stop trails above the lows.
+    Short,
+    // Holding the defensive asset, closed on the next buy signal.
+    Defensive,
+}
+
+// Position values written whenever the position changes and on shutdown, so
+// a restart or a crash picks the position back up.
+#[derive(Debug, Serialize, Deserialize)]
+struct PositionState {
+    can_trade: bool,
+    stop_loss: f64,
+    take_profit: f64,
+    last_high: f64,
+    last_atr: f64,
+    #[serde(default)]
+    position: PositionSide,
+    #[serde(default)]
+    last_low: f64,
+    #[serde(default)]
+    open_size: f64,
+    #[serde(default)]
+    dca_size: f64,
+    #[serde(default)]
+    dca_last_buy: i64,
+    // Coin bought by the pair traders, held back from strategy sells like the
+    // dca holdings. Only the pairs thread writes it.
+    #[serde(default)]
+    pair_size: f64,
+}
+
+impl Default for PositionState {
+    fn default() -> Self {
+        PositionState {
+            can_trade: true,
+            stop_loss: 0.0,
+            take_profit: 0.0,
+            last_high: 0.0,
+            last_atr: 0.0,
+            position: PositionSide::Long,
+            last_low: 0.0,
+            open_size: 0.0,
+            dca_size: 0.0,
+            dca_last_buy: 0,
+            pair_size: 0.0,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct BotAccount {
     client: reqwest::blocking::Client,
@@ -53,15 +141,28 @@ pub struct BotAccount {
     market_fee: f64,
     taker_fee: f64,
     div_num: usize, // The amount to which to divide total usdc by.
+    mode: TradeMode,
     can_trade: bool,
+    position: PositionSide,
     symbol_id: Option<String>,
     usdc_id: Option<String>,
     stop_loss: f64,
+    take_profit: f64,
     last_high: f64,
+    last_low: f64,
+    last_atr: f64,
+    // Base size of an open short or defensive holding, bought back or sold
+    // to close it.
+    open_size: f64,
+    // Coin bought by the dca schedule, held back from strategy sells.
+    dca_size: f64,
+    dca_last_buy: i64,
+    pending_order_id: Option<String>,
+    close_retry_at: Option<std::time::Instant>,
 }
 
 impl BotAccount {
-    pub fn new(num_symbols: usize) -> Self {
+    pub fn new(num_symbols: usize, mode: TradeMode) -> Self {
         dotenv::dotenv().ok();
         let api_key = std::env::var("API_KEY").expect("API_KEY not found in environment");
         let secret_key = std::env::var("API_SECRET").expect("SECRET_KEY not found in environment");
@@ -75,11 +176,21 @@ impl BotAccount {
             market_fee: 0.0,
             taker_fee: 0.0,
             div_num: num_symbols,
+            mode,
             can_trade: true,
+            position: PositionSide::Long,
             symbol_id: None,
             usdc_id: None,
             stop_loss: 0.0,
+            take_profit: 0.0,
             last_high: 0.0,
+            last_low: 0.0,
+            last_atr: 0.0,
+            open_size: 0.0,
+            dca_size: 0.0,
+            dca_last_buy: 0,
+            pending_order_id: None,
+            close_retry_at: None,
         }
     }
 
@@ -152,14 +263,32 @@ impl BotAccount {
     }
 
     pub fn get_product(&self, symbol: CoinSymbol) -> Product {
-        let path = get_api_string(symbol, CoinSymbol::Usdc, PRODUCT_REQUEST_PATH);
+        self.get_quote_product(symbol, CoinSymbol::Usdc)
+    }
+
+    pub fn get_quote_product(&self, symbol: CoinSymbol, quote: CoinSymbol) -> Product {
+        let path = get_api_string(symbol, quote, PRODUCT_REQUEST_PATH);
 
         let headers = create_headers(self.secret_key.as_bytes(), &self.api_key, "GET", &path, "");
-        let url = get_api_string(symbol, CoinSymbol::Usdc, PRODUCT_API_URL);
+        let url = get_api_string(symbol, quote, PRODUCT_API_URL);
 
         send_get_request::<Product>(&self.client, &url, headers).expect("Failed to get product")
     }
 
+    // Any product by id, e.g. a perp like "BTC-PERP-INTX" or a defensive asset.
+    pub fn get_product_by_id(&self, product_id: &str) -> Option<Product> {
+        let path = format!("{}/{}", PRODUCT_REQUEST_PATH, product_id);
+
+        let headers = create_headers(self.secret_key.as_bytes(), &self.api_key, "GET", &path, "");
+        let url = format!("{}/{}", PRODUCT_API_URL, product_id);
+
+        let product = send_get_request::<Product>(&self.client, &url, headers).ok();
+        if product.is_none() {
+            println!("Failed to get product {}", product_id);
+        }
+        product
+    }
+
     fn get_transaction_summary(&self) -> FeeData {
         let headers = create_headers(
             self.secret_key.as_bytes(),
@@ -188,9 +317,19 @@ impl BotAccount {
     }
 
     pub fn create_order(&mut self, order_type: TradeSide, symbol: CoinSymbol, atr: f64, high: f64) {
-        let client_order_id = Uuid::new_v4().to_string();
+        let mut amount = self.get_currency_amount(order_type, symbol);
 
-        let amount = self.get_currency_amount(order_type, symbol);
+        // Coin bought by the dca schedule or the pair traders is never sold
+        // by the strategy.
+        if order_type == TradeSide::Sell {
+            let places = self.get_coin_places(&symbol, order_type);
+            let pair_size = Self::read_state(symbol).map_or(0.0, |state| state.pair_size);
+            amount = self.truncate_to_decimal_places(amount - self.dca_size - pair_size, places);
+            if amount <= 0.0 {
+                println!("Nothing to sell outside of the dca and pair holdings");
+                return;
+            }
+        }
 
         println!("Amount: {}", amount);
 
@@ -214,16 +353,130 @@ impl BotAccount {
             String::from(CoinSymbol::Usdc)
         );
 
+        if self
+            .send_market_order(&product_id, order_type, quote_size, base_size)
+            .is_none()
+        {
+            return;
+        }
+
+        match order_type {
+            TradeSide::Buy => {
+                self.can_trade = false;
+                self.position = PositionSide::Long;
+                self.stop_loss = high - atr;
+                self.take_profit = high + atr * TAKE_PROFIT_ATR_MODIFIER;
+                self.last_high = high;
+                self.last_atr = atr;
+            }
+            TradeSide::Sell => self.clear_position(),
+        }
+        self.save_state(symbol);
+    }
+
+    // Buys quote_amount worth of the coin for the dca schedule. The size bought
+    // is estimated from the price so it can be kept out of strategy sells.
+    pub fn create_dca_order(
+        &mut self,
+        symbol: CoinSymbol,
+        quote_amount: f64,
+        price: f64,
+        time: i64,
+    ) {
+        let places = self.get_coin_places(&symbol, TradeSide::Buy);
+        let amount = self.truncate_to_decimal_places(quote_amount, places);
+        let available = self.get_currency_balance(&String::from(CoinSymbol::Usdc));
+
+        // Skipped rather than retried so a short balance doesn't buy late.
+        self.dca_last_buy = time;
+
+        if amount <= 0.0 || available < amount {
+            println!(
+                "{:?} dca buy of {} skipped, {} USDC available",
+                symbol, amount, available
+            );
+            self.save_state(symbol);
+            return;
+        }
+
+        let product_id = format!(
+            "{}-{}",
+            String::from(symbol),
+            String::from(CoinSymbol::Usdc)
+        );
+
+        if self
+            .send_market_order(&product_id, TradeSide::Buy, Some(amount.to_string()), None)
+            .is_some()
+        {
+            self.dca_size += amount / price * (1.0 - self.taker_fee);
+            println!(
+                "{:?} dca bought {} USDC at {}, holding: {}",
+                symbol, amount, price, self.dca_size
+            );
+        }
+        self.save_state(symbol);
+    }
+
+    #[inline]
+    pub fn get_dca_last_buy(&self) -> i64 {
+        self.dca_last_buy
+    }
+
+    pub fn send_market_order(
+        &mut self,
+        product_id: &str,
+        order_type: TradeSide,
+        quote_size: Option<String>,
+        base_size: Option<String>,
+    ) -> Option<OrderResponse> {
+        let order_configuration = serde_json::json!({
+            "market_market_ioc":{
+                "quote_size":  quote_size,
+                "base_size":  base_size
+            }
+        });
+
+        let order = self.send_order(product_id, order_type, order_configuration)?;
+        self.pending_order_id = Some(order.order_id.clone());
+        Some(order)
+    }
+
+    // Good till cancelled limit order, returns the order id. Limit orders rest
+    // on the book so they aren't waited on at shutdown.
+    pub fn create_limit_order(
+        &self,
+        product_id: &str,
+        order_type: TradeSide,
+        base_size: Decimal,
+        limit_price: Decimal,
+        post_only: bool,
+    ) -> Option<String> {
+        let order_configuration = serde_json::json!({
+            "limit_limit_gtc":{
+                "base_size": base_size.to_string(),
+                "limit_price": limit_price.to_string(),
+                "post_only": post_only
+            }
+        });
+
+        self.send_order(product_id, order_type, order_configuration)
+            .map(|order| order.order_id)
+    }
+
+    fn send_order(
+        &self,
+        product_id: &str,
+        order_type: TradeSide,
+        order_configuration: serde_json::Value,
+    ) -> Option<OrderResponse> {
+        let client_order_id = Uuid::new_v4().to_string();
+
         let body = serde_json::json!({
             "client_order_id": client_order_id,
             "product_id":product_id,
             "side": order_type,
-            "order_configuration":{
-                "market_market_ioc":{
-                    "quote_size":  quote_size,
-                    "base_size":  base_size
-                }
-            }
+            "order_configuration": order_configuration
         });
 
         let headers = create_headers(
@@ -244,19 +497,406 @@ impl BotAccount {
             .json()
             .expect("Failed to read json");
 
-        if order.success {
-            match order_type {
-                TradeSide::Buy => {
-                    self.can_trade = false;
-                    self.stop_loss = high - atr;
-                    self.last_high = high;
+        if !order.success {
+            println!("{} order failed: {}", product_id, order.failure_reason);
+            return None;
+        }
+
+        Some(order)
+    }
+
+    fn clear_position(&mut self) {
+        self.can_trade = true;
+        self.position = PositionSide::Long;
+        self.stop_loss = 0.0;
+        self.take_profit = 0.0;
+        self.last_high = 0.0;
+        self.last_low = 0.0;
+        self.last_atr = 0.0;
+        self.open_size = 0.0;
+    }
+
+    // Uses a sell signal to short or rotate into the defensive asset,
+    // depending on the mode. Does nothing in long only mode.
+    pub fn open_sell_side(&mut self, symbol: CoinSymbol, atr: f64, low: f64, price: f64) {
+        match self.mode.clone() {
+            TradeMode::LongOnly => (),
+            TradeMode::Rotate { asset } => {
+                let amount = self.get_currency_amount(TradeSide::Buy, symbol);
+                let product_id = format!("{}-{}", asset, String::from(CoinSymbol::Usdc));
+                let product = match self.get_product_by_id(&product_id) {
+                    Some(product) if product.price > 0.0 => product,
+                    _ => return,
+                };
+
+                if self
+                    .send_market_order(&product_id, TradeSide::Buy, Some(amount.to_string()), None)
+                    .is_some()
+                {
+                    self.open_defensive(amount, product.price);
+                    self.save_state(symbol);
+                }
+            }
+            TradeMode::Short { product_id } => {
+                if price <= 0.0 {
+                    return;
+                }
+                let product = match self.get_product_by_id(&product_id) {
+                    Some(product) => product,
+                    None => return,
+                };
+                let amount = self.get_currency_amount(TradeSide::Buy, symbol);
+                let size = match short_size(&product, amount, price) {
+                    Some(size) => size,
+                    None => {
+                        println!("{} short size is under the minimum", product_id);
+                        return;
+                    }
+                };
+
+                if self
+                    .send_market_order(&product_id, TradeSide::Sell, None, Some(size.to_string()))
+                    .is_some()
+                {
+                    self.open_short(size, atr, low);
+                    self.save_state(symbol);
+                }
+            }
+        }
+    }
+
+    // Records a filled buy of the defensive asset. The size is estimated from
+    // the product price, only this much is sold on close so other holdings of
+    // the asset are left alone.
+    fn open_defensive(&mut self, amount: f64, price: f64) {
+        self.can_trade = false;
+        self.position = PositionSide::Defensive;
+        self.open_size = amount / price * (1.0 - self.taker_fee);
+    }
+
+    // Records a filled short, the stop and take profit mirror a long's.
+    fn open_short(&mut self, size: f64, atr: f64, low: f64) {
+        self.can_trade = false;
+        self.position = PositionSide::Short;
+        self.open_size = size;
+        self.stop_loss = low + atr;
+        self.take_profit = low - atr * TAKE_PROFIT_ATR_MODIFIER;
+        self.last_low = low;
+        self.last_atr = atr;
+    }
+
+    // Closes whichever position is open.
+    pub fn close_position(&mut self, symbol: CoinSymbol, atr: f64, price: f64) {
+        if self.can_trade {
+            return;
+        }
+
+        match (self.position, self.mode.clone()) {
+            (PositionSide::Long, _) => self.create_order(TradeSide::Sell, symbol, atr, price),
+            (PositionSide::Short, TradeMode::Short { product_id }) => {
+                let size = self.open_size.to_string();
+
+                if self
+                    .send_market_order(&product_id, TradeSide::Buy, None, Some(size))
+                    .is_some()
+                {
+                    self.clear_position();
+                    self.save_state(symbol);
                 }
-                TradeSide::Sell => {
-                    self.can_trade = true;
-                    self.stop_loss = 0.0;
-                    self.last_high = 0.0;
+            }
+            (PositionSide::Defensive, TradeMode::Rotate { asset }) => {
+                // State saved before the size was tracked sells the balance.
+                let balance = self.get_currency_balance(&asset);
+                let held = if self.open_size > 0.0 {
+                    self.open_size.min(balance)
+                } else {
+                    balance
+                };
+                let size = self.truncate_to_decimal_places(held, DEFENSIVE_SELL_PLACES);
+                let product_id = format!("{}-{}", asset, String::from(CoinSymbol::Usdc));
+
+                if self
+                    .send_market_order(&product_id, TradeSide::Sell, None, Some(size.to_string()))
+                    .is_some()
+                {
+                    self.clear_position();
+                    self.save_state(symbol);
+                }
+            }
+            (position, mode) => println!(
+                "Can't close {:?} position in {:?} mode, close it manually",
+                position, mode
+            ),
+        }
+
+        self.close_retry_at =
+            (!self.can_trade).then(|| std::time::Instant::now() + CLOSE_RETRY_INTERVAL);
+    }
+
+    #[inline]
+    pub fn get_position(&self) -> PositionSide {
+        self.position
+    }
+
+    #[inline]
+    pub fn trades_sell_side(&self) -> bool {
+        self.mode != TradeMode::LongOnly
+    }
+
+    pub fn get_currency_balance(&self, currency: &str) -> f64 {
+        self.get_wallet()
+            .accounts
+            .iter()
+            .find(|account| account.available_balance.currency == currency)
+            .map_or(0.0, |account| account.available_balance.value)
+    }
+
+    // What another thread can sell of a coin without touching the dca and pair
+    // holdings or the strategy's long, which sells everything outside them.
+    // Goes by the state last saved by the coin's own thread.
+    pub fn get_free_coin_balance(&self, symbol: CoinSymbol) -> f64 {
+        let balance = self.get_currency_balance(&String::from(symbol));
+
+        match Self::read_state(symbol) {
+            Some(state) if !state.can_trade && state.position == PositionSide::Long => 0.0,
+            Some(state) => (balance - state.dca_size - state.pair_size).max(0.0),
+            None => balance,
+        }
+    }
+
+    // Converts between two currencies, e.g. USD and USDC, by taking a convert
+    // quote and committing it straight away.
+    pub fn convert(&self, from: CoinSymbol, to: CoinSymbol, amount: f64) -> bool {
+        let wallet = self.get_wallet();
+        let find_account = |symbol: CoinSymbol| {
+            wallet
+                .accounts
+                .iter()
+                .find(|account| account.currency == String::from(symbol))
+                .map(|account| account.uuid.clone())
+        };
+
+        let (from_account, to_account) = match (find_account(from), find_account(to)) {
+            (Some(from_account), Some(to_account)) => (from_account, to_account),
+            _ => {
+                println!("No account to convert {:?} to {:?}", from, to);
+                return false;
+            }
+        };
+
+        let amount = self.truncate_to_decimal_places(amount, USDC_BUY_PLACES);
+        let body = serde_json::json!({
+            "from_account": from_account,
+            "to_account": to_account,
+            "amount": amount.to_string()
+        });
+
+        let quote = match self.send_post_request::<ConvertResponse>(
+            CONVERT_QUOTE_REQUEST_PATH,
+            CONVERT_QUOTE_API_URL,
+            &body,
+        ) {
+            Some(quote) => quote,
+            None => return false,
+        };
+
+        let path = format!("{}/{}", CONVERT_TRADE_REQUEST_PATH, quote.trade.id);
+        let url = format!("{}/{}", CONVERT_TRADE_API_URL, quote.trade.id);
+        let body = serde_json::json!({
+            "from_account": from_account,
+            "to_account": to_account
+        });
+
+        match self.send_post_request::<ConvertResponse>(&path, &url, &body) {
+            Some(trade) => {
+                println!(
+                    "Converted {} {:?} to {:?}: {}",
+                    amount, from, to, trade.trade.status
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn send_post_request<T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        url: &str,
+        body: &serde_json::Value,
+    ) -> Option<T> {
+        let headers = create_headers(
+            self.secret_key.as_bytes(),
+            &self.api_key,
+            "POST",
+            path,
+            &body.to_string(),
+        );
+
+        let response = self
+            .client
+            .post(url)
+            .headers(headers)
+            .json(body)
+            .send()
+            .and_then(|res| res.json::<T>());
+
+        match response {
+            Ok(response) => Some(response),
+            Err(e) => {
+                println!("Request to {} failed: {}", path, e);
+                None
+            }
+        }
+    }
+
+    pub fn get_order(&self, order_id: &str) -> Option<OrderSummary> {
+        let path = format!("{}/{}", ORDER_HISTORY_REQUEST_PATH, order_id);
+        let headers = create_headers(self.secret_key.as_bytes(), &self.api_key, "GET", &path, "");
+        let url = format!("{}/{}", ORDER_HISTORY_API_URL, order_id);
+
+        send_get_request::<SingleOrder>(&self.client, &url, headers)
+            .ok()
+            .map(|order| order.order)
+    }
+
+    // Polls the last placed order until coinbase reports it as settled.
+    pub fn wait_for_orders(&mut self) {
+        let order_id = match self.pending_order_id.take() {
+            Some(order_id) => order_id,
+            None => return,
+        };
+
+        for _ in 0..ORDER_SETTLE_ATTEMPTS {
+            match self.get_order(&order_id) {
+                Some(order) if order.status.is_settled() => {
+                    println!(
+                        "Order {} on {} settled: {:?}",
+                        order_id, order.product_id, order.status
+                    );
+                    return;
+                }
+                _ => std::thread::sleep(ORDER_SETTLE_WAIT),
+            }
+        }
+
+        println!("Order {} did not settle before shutdown", order_id);
+    }
+
+    pub fn get_open_orders(&self, symbol: CoinSymbol) -> Vec<OrderSummary> {
+        let path = format!("{}/batch", ORDER_HISTORY_REQUEST_PATH);
+        let headers = create_headers(self.secret_key.as_bytes(), &self.api_key, "GET", &path, "");
+        let url = format!(
+            "{}/batch?product_id={}-{}&order_status=OPEN",
+            ORDER_HISTORY_API_URL,
+            String::from(symbol),
+            String::from(CoinSymbol::Usdc)
+        );
+
+        send_get_request::<OrderList>(&self.client, &url, headers)
+            .map(|list| list.orders)
+            .unwrap_or_default()
+    }
+
+    pub fn cancel_orders(&self, order_ids: &[String]) {
+        if order_ids.is_empty() {
+            return;
+        }
+
+        let body = serde_json::json!({ "order_ids": order_ids });
+
+        let headers = create_headers(
+            self.secret_key.as_bytes(),
+            &self.api_key,
+            "POST",
+            CANCEL_REQUEST_PATH,
+            &body.to_string(),
+        );
+
+        let response = self
+            .client
+            .post(CANCEL_API_URL)
+            .headers(headers)
+            .json(&body)
+            .send()
+            .and_then(|res| res.json::<CancelOrdersResponse>());
+
+        match response {
+            Ok(response) => {
+                for result in response.results.iter() {
+                    println!(
+                        "Cancel order {}, success: {}, reason: {}",
+                        result.order_id, result.success, result.failure_reason
+                    );
                 }
             }
+            Err(e) => println!("Failed to cancel orders: {}", e),
+        }
+    }
+
+    fn state_file(symbol: CoinSymbol) -> String {
+        format!("{}_state.json", String::from(symbol).to_lowercase())
+    }
+
+    pub fn save_state(&self, symbol: CoinSymbol) {
+        let _lock = STATE_FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let pair_size = Self::read_state(symbol).map_or(0.0, |saved| saved.pair_size);
+
+        let state = PositionState {
+            can_trade: self.can_trade,
+            stop_loss: self.stop_loss,
+            take_profit: self.take_profit,
+            last_high: self.last_high,
+            last_atr: self.last_atr,
+            position: self.position,
+            last_low: self.last_low,
+            open_size: self.open_size,
+            dca_size: self.dca_size,
+            dca_last_buy: self.dca_last_buy,
+            pair_size,
+        };
+        Self::write_state(symbol, &state);
+    }
+
+    // Records what the pair traders hold of a coin, keeping the rest of the
+    // state the coin's thread saved.
+    pub fn save_pair_size(symbol: CoinSymbol, pair_size: f64) {
+        let _lock = STATE_FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let state = Self::read_state(symbol).unwrap_or_default();
+        Self::write_state(symbol, &PositionState { pair_size, ..state });
+    }
+
+    fn write_state(symbol: CoinSymbol, state: &PositionState) {
+        let result = serde_json::to_string_pretty(state)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                std::fs::write(Self::state_file(symbol), json).map_err(|e| e.to_string())
+            });
+
+        if let Err(e) = result {
+            println!("Failed to save state: {}", e);
+        }
+    }
+
+    fn read_state(symbol: CoinSymbol) -> Option<PositionState> {
+        std::fs::read_to_string(Self::state_file(symbol))
+            .ok()
+            .and_then(|json| serde_json::from_str::<PositionState>(&json).ok())
+    }
+
+    pub fn load_state(&mut self, symbol: CoinSymbol) {
+        if let Some(state) = Self::read_state(symbol) {
+            println!("Loaded saved state: {:?}", state);
+            self.can_trade = state.can_trade;
+            self.stop_loss = state.stop_loss;
+            self.take_profit = state.take_profit;
+            self.last_high = state.last_high;
+            self.last_atr = state.last_atr;
+            self.position = state.position;
+            self.last_low = state.last_low;
+            self.open_size = state.open_size;
+            self.dca_size = state.dca_size;
+            self.dca_last_buy = state.dca_last_buy;
         }
     }
 
@@ -327,9 +967,29 @@ impl BotAccount {
         self.can_trade
     }
 
-    pub fn update_coin_position(&mut self, high: f64, atr: f64) -> bool {
+    #[inline]
+    pub fn get_maker_fee(&self) -> f64 {
+        self.market_fee
+    }
+
+    #[inline]
+    pub fn get_taker_fee(&self) -> f64 {
+        self.taker_fee
+    }
+
+    pub fn update_coin_position(&mut self, high: f64, low: f64, atr: f64) -> bool {
+        self.last_atr = atr;
+
+        match self.position {
+            PositionSide::Long => (),
+            PositionSide::Short => return self.update_short_position(high, low, atr),
+            PositionSide::Defensive => return false,
+        }
+
+        // The stop only ever moves up, a strategy or level stop set on entry
+        // can sit above high - atr.
         if high > self.last_high {
-            self.stop_loss = high - atr;
+            self.stop_loss = self.stop_loss.max(high - atr);
             self.last_high = high;
 
             println!(
@@ -345,11 +1005,101 @@ impl BotAccount {
             );
             return true;
         }
+
+        if self.take_profit > 0.0 && high >= self.take_profit {
+            println!("SELL, HIGH: {}, TAKE PROFIT: {}", high, self.take_profit);
+            return true;
+        }
         false
     }
+
+    // Mirror of the long trailing stop, the stop follows new lows down and
+    // the position closes when price rises back through it.
+    fn update_short_position(&mut self, high: f64, low: f64, atr: f64) -> bool {
+        if low < self.last_low {
+            self.stop_loss = self.stop_loss.min(low + atr);
+            self.last_low = low;
+
+            println!("LOW: {}, STOP LOSS: {}", low, self.stop_loss);
+        }
+
+        if high >= self.stop_loss {
+            println!(
+                "COVER, HIGH: {}, LAST LOW: {}, STOP LOSS: {}",
+                high, self.last_low, self.stop_loss
+            );
+            return true;
+        }
+
+        if low <= self.take_profit {
+            println!("COVER, LOW: {}, TAKE PROFIT: {}", low, self.take_profit);
+            return true;
+        }
+        false
+    }
+
+    // Checks the open position against a live ticker price between candle closes,
+    // trailing the stop with the atr from the last closed candle.
+    pub fn update_live_price(&mut self, price: f64) -> bool {
+        // A failed close waits out CLOSE_RETRY_INTERVAL before the next try.
+        if self
+            .close_retry_at
+            .is_some_and(|retry_at| std::time::Instant::now() < retry_at)
+        {
+            return false;
+        }
+
+        match (self.can_trade, self.position) {
+            (true, _) | (false, PositionSide::Defensive) => return false,
+            (false, PositionSide::Short) => {
+                return self.update_short_position(price, price, self.last_atr)
+            }
+            (false, PositionSide::Long) => (),
+        }
+
+        if price > self.last_high {
+            self.stop_loss = self.stop_loss.max(price - self.last_atr);
+            self.last_high = price;
+        }
+
+        if price <= self.stop_loss {
+            println!(
+                "SELL, PRICE: {}, LAST HIGH: {}, STOP LOSS: {}",
+                price, self.last_high, self.stop_loss
+            );
+            return true;
+        }
+
+        if self.take_profit > 0.0 && price >= self.take_profit {
+            println!("SELL, PRICE: {}, TAKE PROFIT: {}", price, self.take_profit);
+            return true;
+        }
+        false
+    }
+
+    pub fn set_stop_loss(&mut self, stop_loss: f64) {
+        self.stop_loss = stop_loss;
+    }
+
+    #[inline]
+    pub fn get_last_atr(&self) -> f64 {
+        self.last_atr
+    }
+}
+
+// Base size to short for a quote amount, None when it rounds down below the
+// product's minimum.
+fn short_size(product: &Product, amount: f64, price: f64) -> Option<f64> {
+    let size = product.round_base(amount / price)?.to_f64()?;
+    (size >= product.base_min_size).then_some(size)
 }
 
-pub fn get_product_candle(symbol: CoinSymbol, start: i64, end: i64) -> CandleHistory {
+pub fn get_product_candle(
+    symbol: CoinSymbol,
+    start: i64,
+    end: i64,
+    granularity: &str,
+) -> CandleHistory {
     let client = reqwest::blocking::Client::new();
     let api_string = get_api_string(symbol, CoinSymbol::Usdc, PRODUCT_REQUEST_PATH);
     let api_key = std::env::var("API_KEY").expect("API_KEY not found in environment");
@@ -360,7 +1110,7 @@ pub fn get_product_candle(symbol: CoinSymbol, start: i64, end: i64) -> CandleHis
     let url_string = get_api_string(symbol, CoinSymbol::Usdc, PRODUCT_API_URL);
     let url = format!(
         "{}/candles?start={}&end={}&granularity={}",
-        url_string, start, end, "FIVE_MINUTE"
+        url_string, start, end, granularity
     );
 
     let ans: CandleHistory = client
@@ -373,3 +1123,226 @@ pub fn get_product_candle(symbol: CoinSymbol, start: i64, end: i64) -> CandleHis
 
     ans
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An account with no keys, only for the position logic that stays offline.
+    fn account(mode: TradeMode) -> BotAccount {
+        BotAccount {
+            client: reqwest::blocking::Client::new(),
+            api_key: String::new(),
+            secret_key: String::new(),
+            market_fee: 0.0,
+            taker_fee: 0.0,
+            div_num: 1,
+            mode,
+            can_trade: true,
+            position: PositionSide::Long,
+            symbol_id: None,
+            usdc_id: None,
+            stop_loss: 0.0,
+            take_profit: 0.0,
+            last_high: 0.0,
+            last_low: 0.0,
+            last_atr: 0.0,
+            open_size: 0.0,
+            dca_size: 0.0,
+            dca_last_buy: 0,
+            pending_order_id: None,
+            close_retry_at: None,
+        }
+    }
+
+    // A long entered at a high of 100 with an atr of 5, as create_order leaves it.
+    fn long() -> BotAccount {
+        let mut account = account(TradeMode::LongOnly);
+        account.can_trade = false;
+        account.stop_loss = 95.0;
+        account.last_high = 100.0;
+        account.last_atr = 5.0;
+        account
+    }
+
+    // A short entered at a low of 100 with an atr of 5, as open_sell_side leaves it.
+    fn short() -> BotAccount {
+        let mut account = account(TradeMode::Short {
+            product_id: "BTC-PERP-INTX".to_string(),
+        });
+        account.can_trade = false;
+        account.position = PositionSide::Short;
+        account.stop_loss = 105.0;
+        account.last_low = 100.0;
+        account.last_atr = 5.0;
+        account
+    }
+
+    #[test]
+    fn long_stop_only_moves_up() {
+        let mut account = long();
+        // A strategy stop above high - atr.
+        account.set_stop_loss(98.0);
+
+        assert!(!account.update_coin_position(101.0, 99.0, 5.0));
+        assert_eq!(account.stop_loss, 98.0);
+        assert_eq!(account.last_high, 101.0);
+
+        assert!(!account.update_coin_position(110.0, 104.0, 5.0));
+        assert_eq!(account.stop_loss, 105.0);
+
+        assert!(account.update_coin_position(104.0, 100.0, 5.0));
+        assert_eq!(account.stop_loss, 105.0);
+    }
+
+    #[test]
+    fn short_stop_only_moves_down() {
+        let mut account = short();
+        account.set_stop_loss(103.0);
+
+        assert!(!account.update_coin_position(102.0, 99.0, 5.0));
+        assert_eq!(account.stop_loss, 103.0);
+        assert_eq!(account.last_low, 99.0);
+
+        assert!(!account.update_coin_position(94.0, 90.0, 5.0));
+        assert_eq!(account.stop_loss, 95.0);
+
+        assert!(account.update_coin_position(96.0, 93.0, 5.0));
+        assert_eq!(account.stop_loss, 95.0);
+    }
+
+    #[test]
+    fn live_price_trails_the_long_stop() {
+        let mut account = long();
+
+        assert!(!account.update_live_price(102.0));
+        assert_eq!(account.stop_loss, 97.0);
+        assert_eq!(account.last_high, 102.0);
+
+        // Falling back doesn't move the stop down.
+        assert!(!account.update_live_price(98.0));
+        assert_eq!(account.stop_loss, 97.0);
+
+        assert!(account.update_live_price(97.0));
+    }
+
+    #[test]
+    fn live_price_hits_take_profit_and_short_stops() {
+        let mut account = long();
+        account.take_profit = 115.0;
+        assert!(account.update_live_price(115.0));
+
+        let mut account = short();
+        assert!(!account.update_live_price(96.0));
+        assert_eq!(account.stop_loss, 101.0);
+        assert!(account.update_live_price(101.0));
+    }
+
+    #[test]
+    fn live_price_ignores_flat_and_defensive_positions() {
+        let mut flat = account(TradeMode::LongOnly);
+        assert!(!flat.update_live_price(1.0));
+
+        let mut defensive = long();
+        defensive.position = PositionSide::Defensive;
+        assert!(!defensive.update_live_price(1.0));
+    }
+
+    #[test]
+    fn failed_close_waits_before_retrying() {
+        let mut account = long();
+        let now = std::time::Instant::now();
+
+        account.close_retry_at = Some(now + CLOSE_RETRY_INTERVAL);
+        assert!(!account.update_live_price(90.0));
+        // Nothing moves while backing off.
+        assert!(!account.update_live_price(120.0));
+        assert_eq!(account.last_high, 100.0);
+
+        account.close_retry_at = Some(now);
+        assert!(account.update_live_price(90.0));
+    }
+
+    fn perp() -> Product {
+        serde_json::from_value(serde_json::json!({
+            "product_id": "BTC-PERP-INTX",
+            "price": "30000",
+            "quote_min_size": "10",
+            "quote_max_size": "1000000",
+            "base_min_size": "0.001",
+            "base_max_size": "100",
+            "base_increment": "0.0001",
+            "quote_increment": "0.01",
+            "price_increment": "0.1",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn long_only_ignores_sell_signals() {
+        let mut account = account(TradeMode::LongOnly);
+        account.open_sell_side(CoinSymbol::Btc, 5.0, 100.0, 100.0);
+
+        assert!(account.can_trade);
+        assert_eq!(account.position, PositionSide::Long);
+    }
+
+    #[test]
+    fn rotate_records_the_defensive_size_after_fees() {
+        let mut account = account(TradeMode::Rotate {
+            asset: "PAXG".to_string(),
+        });
+        account.taker_fee = 0.006;
+        account.open_defensive(100.0, 4.0);
+
+        assert!(!account.can_trade);
+        assert_eq!(account.position, PositionSide::Defensive);
+        assert!((account.open_size - 24.85).abs() < 1e-9);
+        // No stop is kept on the defensive asset.
+        assert!(!account.update_coin_position(1.0, 1.0, 5.0));
+        assert!(!account.update_live_price(1.0));
+    }
+
+    #[test]
+    fn short_is_sized_from_the_quote_amount() {
+        let product = perp();
+
+        // 1000 / 30000 rounded down to the 0.0001 increment.
+        assert_eq!(short_size(&product, 1000.0, 30_000.0), Some(0.0333));
+        // Rounds to 0.0009, under the 0.001 minimum.
+        assert_eq!(short_size(&product, 29.0, 30_000.0), None);
+    }
+
+    #[test]
+    fn short_without_a_price_is_skipped() {
+        let mut account = account(TradeMode::Short {
+            product_id: "BTC-PERP-INTX".to_string(),
+        });
+        account.open_sell_side(CoinSymbol::Btc, 5.0, 100.0, 0.0);
+
+        assert!(account.can_trade);
+    }
+
+    #[test]
+    fn short_trails_its_stop_down_until_hit() {
+        let mut account = account(TradeMode::Short {
+            product_id: "BTC-PERP-INTX".to_string(),
+        });
+        account.open_short(0.5, 5.0, 100.0);
+
+        assert!(!account.can_trade);
+        assert_eq!(account.position, PositionSide::Short);
+        assert_eq!(account.open_size, 0.5);
+        assert_eq!(account.stop_loss, 105.0);
+        assert_eq!(account.take_profit, 85.0);
+
+        // New lows pull the stop down, a bounce leaves it where it is.
+        assert!(!account.update_coin_position(99.0, 96.0, 5.0));
+        assert_eq!(account.stop_loss, 101.0);
+        assert!(!account.update_coin_position(100.0, 97.0, 5.0));
+        assert_eq!(account.stop_loss, 101.0);
+
+        // A high through the stop closes it.
+        assert!(account.update_coin_position(101.5, 98.0, 5.0));
+    }
+}
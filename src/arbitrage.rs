@@ -0,0 +1,457 @@
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use rust_decimal::prelude::ToPrimitive;
+use serde::Deserialize;
+
+use crate::{
+    account::BotAccount,
+    coin::CoinSymbol,
+    model::{account::Product, event::Ticker, TradeSide},
+};
+
+// Quotes older than this aren't compared, the other book may have moved.
+const QUOTE_MAX_AGE: Duration = Duration::from_secs(5);
+const ARBITRAGE_LOG_INTERVAL: Duration = Duration::from_secs(60);
+// Gives the balances time to settle before trading the same gap again.
+const ARBITRAGE_COOLDOWN: Duration = Duration::from_secs(60);
+// Wait after a gap that couldn't be traded, so the wallet isn't checked on
+// every ticker.
+const ARBITRAGE_RETRY: Duration = Duration::from_secs(10);
+
+const DEFAULT_ARBITRAGE_MARGIN: f64 = 0.001;
+const DEFAULT_ARBITRAGE_QUOTE_AMOUNT: f64 = 50.0;
+
+fn default_margin() -> f64 {
+    DEFAULT_ARBITRAGE_MARGIN
+}
+
+fn default_quote_amount() -> f64 {
+    DEFAULT_ARBITRAGE_QUOTE_AMOUNT
+}
+
+// Watches the USD and USDC books of each coin, leaving symbols empty watches
+// every traded coin. margin is the edge needed on top of fees, as a fraction.
+// convert_fee is the fraction lost converting the proceeds back, USD and USDC
+// normally convert 1:1.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArbitrageConfig {
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    #[serde(default = "default_margin")]
+    pub margin: f64,
+    #[serde(default)]
+    pub execute: bool,
+    #[serde(default = "default_quote_amount")]
+    pub quote_amount: f64,
+    #[serde(default)]
+    pub convert_fee: f64,
+}
+
+impl ArbitrageConfig {
+    pub fn get_symbols(&self, traded: &[CoinSymbol]) -> Vec<CoinSymbol> {
+        if self.symbols.is_empty() {
+            return traded.to_vec();
+        }
+
+        self.symbols
+            .iter()
+            .filter_map(|symbol| CoinSymbol::from_str(&symbol.to_uppercase()).ok())
+            .filter(|symbol| !matches!(symbol, CoinSymbol::Usd | CoinSymbol::Usdc))
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.margin <= 0.0 {
+            return Err(format!(
+                "arbitrage margin must be positive, got {}",
+                self.margin
+            ));
+        }
+
+        if !(0.0..1.0).contains(&self.convert_fee) {
+            return Err(format!(
+                "arbitrage convert_fee must be in [0, 1), got {}",
+                self.convert_fee
+            ));
+        }
+
+        if self.quote_amount <= 0.0 {
+            return Err(format!(
+                "arbitrage quote_amount must be positive, got {}",
+                self.quote_amount
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Quote {
+    bid: f64,
+    ask: f64,
+    time: Instant,
+}
+
+impl Quote {
+    fn from_ticker(ticker: &Ticker) -> Self {
+        Quote {
+            bid: ticker.best_bid.unwrap_or(ticker.price),
+            ask: ticker.best_ask.unwrap_or(ticker.price),
+            time: Instant::now(),
+        }
+    }
+}
+
+// Buying on one quote currency and selling on the other, edge is the return
+// on the quote spent after both taker fees and converting the proceeds back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Discrepancy {
+    pub buy_quote: CoinSymbol,
+    pub sell_quote: CoinSymbol,
+    pub buy_price: f64,
+    pub sell_price: f64,
+    pub edge: f64,
+}
+
+#[derive(Debug)]
+struct QuoteBook {
+    product: Product,
+    quote: Option<Quote>,
+}
+
+impl QuoteBook {
+    fn fresh_quote(&self) -> Option<Quote> {
+        self.quote
+            .filter(|quote| quote.time.elapsed() < QUOTE_MAX_AGE)
+    }
+}
+
+#[derive(Debug)]
+pub struct ArbitrageMonitor {
+    symbol: CoinSymbol,
+    config: ArbitrageConfig,
+    usd: QuoteBook,
+    usdc: QuoteBook,
+    last_log: Option<Instant>,
+    next_trade: Option<Instant>,
+}
+
+impl ArbitrageMonitor {
+    pub fn new(symbol: CoinSymbol, config: ArbitrageConfig, account: &BotAccount) -> Self {
+        ArbitrageMonitor {
+            symbol,
+            config,
+            usd: QuoteBook {
+                product: account.get_quote_product(symbol, CoinSymbol::Usd),
+                quote: None,
+            },
+            usdc: QuoteBook {
+                product: account.get_quote_product(symbol, CoinSymbol::Usdc),
+                quote: None,
+            },
+            last_log: None,
+            next_trade: None,
+        }
+    }
+
+    pub fn get_product_ids(&self) -> [String; 2] {
+        [
+            self.usd.product.product_id.clone(),
+            self.usdc.product.product_id.clone(),
+        ]
+    }
+
+    pub fn on_ticker(&mut self, ticker: &Ticker, account: &mut BotAccount) {
+        if ticker.product_id == self.usd.product.product_id {
+            self.usd.quote = Some(Quote::from_ticker(ticker));
+        } else if ticker.product_id == self.usdc.product.product_id {
+            self.usdc.quote = Some(Quote::from_ticker(ticker));
+        } else {
+            return;
+        }
+
+        let discrepancy =
+            match self.get_discrepancy(account.get_taker_fee(), self.config.convert_fee) {
+                Some(discrepancy) => discrepancy,
+                None => return,
+            };
+
+        if self
+            .last_log
+            .is_none_or(|last| last.elapsed() >= ARBITRAGE_LOG_INTERVAL)
+        {
+            self.last_log = Some(Instant::now());
+            println!(
+                "{:?} best USD/USDC gap: buy {:?} at {}, sell {:?} at {}, edge after fees: {:.4}%",
+                self.symbol,
+                discrepancy.buy_quote,
+                discrepancy.buy_price,
+                discrepancy.sell_quote,
+                discrepancy.sell_price,
+                discrepancy.edge * 100.0
+            );
+        }
+
+        if discrepancy.edge <= self.config.margin {
+            return;
+        }
+
+        if self.next_trade.is_some_and(|next| Instant::now() < next) {
+            return;
+        }
+
+        println!(
+            "{:?} arbitrage: buy {:?} at {}, sell {:?} at {}, edge: {:.4}%",
+            self.symbol,
+            discrepancy.buy_quote,
+            discrepancy.buy_price,
+            discrepancy.sell_quote,
+            discrepancy.sell_price,
+            discrepancy.edge * 100.0
+        );
+
+        // The cooldown only starts once an order has moved the balances, when
+        // only watching it keeps the log down.
+        let wait = if self.config.execute && !self.execute(discrepancy, account) {
+            ARBITRAGE_RETRY
+        } else {
+            ARBITRAGE_COOLDOWN
+        };
+        self.next_trade = Some(Instant::now() + wait);
+    }
+
+    // The better of the two directions, once both books have a fresh quote.
+    // The buy fee comes out of the quote spent, the sell fee out of the
+    // proceeds and the convert fee out of what is converted back.
+    fn get_discrepancy(&self, taker_fee: f64, convert_fee: f64) -> Option<Discrepancy> {
+        let usd = self.usd.fresh_quote()?;
+        let usdc = self.usdc.fresh_quote()?;
+        let kept = (1.0 - taker_fee).powi(2) * (1.0 - convert_fee);
+
+        let discrepancy = |buy_quote, buy: Quote, sell_quote, sell: Quote| Discrepancy {
+            buy_quote,
+            sell_quote,
+            buy_price: buy.ask,
+            sell_price: sell.bid,
+            edge: sell.bid / buy.ask * kept - 1.0,
+        };
+
+        let usdc_to_usd = discrepancy(CoinSymbol::Usdc, usdc, CoinSymbol::Usd, usd);
+        let usd_to_usdc = discrepancy(CoinSymbol::Usd, usd, CoinSymbol::Usdc, usdc);
+
+        Some(if usdc_to_usd.edge >= usd_to_usdc.edge {
+            usdc_to_usd
+        } else {
+            usd_to_usdc
+        })
+    }
+
+    // Buys quote_amount on the cheap book, sells what that bought on the rich
+    // one and converts the proceeds back so both balances end where they
+    // started. If the sell fails the coin is sold back on the cheap book.
+    // Returns true once an order has gone out.
+    fn execute(&self, discrepancy: Discrepancy, account: &mut BotAccount) -> bool {
+        let (buy, sell) = match discrepancy.buy_quote {
+            CoinSymbol::Usd => (&self.usd.product, &self.usdc.product),
+            _ => (&self.usdc.product, &self.usd.product),
+        };
+        let taker_fee = account.get_taker_fee();
+
+        // Market buys are sized in quote and the fee comes out of that quote.
+        let quote = buy
+            .round_quote(self.config.quote_amount)
+            .filter(|quote| quote.to_f64().unwrap_or(0.0) >= buy.quote_min_size);
+        let size = quote
+            .and_then(|quote| {
+                let bought = quote.to_f64()? / discrepancy.buy_price * (1.0 - taker_fee);
+                buy.round_base(bought)
+                    .and_then(|size| sell.round_base(size.to_f64()?))
+            })
+            .filter(|size| size.to_f64().unwrap_or(0.0) >= sell.base_min_size);
+        let (quote, size) = match quote.zip(size) {
+            Some(order) => order,
+            None => {
+                println!("{:?} arbitrage size is under the minimum", self.symbol);
+                return false;
+            }
+        };
+
+        let available = account.get_currency_balance(&String::from(discrepancy.buy_quote));
+        if available < quote.to_f64().unwrap_or(f64::MAX) {
+            println!(
+                "{:?} arbitrage needs {} {:?}, holding {}",
+                self.symbol, quote, discrepancy.buy_quote, available
+            );
+            return false;
+        }
+
+        if account
+            .send_market_order(
+                &buy.product_id,
+                TradeSide::Buy,
+                Some(quote.to_string()),
+                None,
+            )
+            .is_none()
+        {
+            return false;
+        }
+
+        if account
+            .send_market_order(
+                &sell.product_id,
+                TradeSide::Sell,
+                None,
+                Some(size.to_string()),
+            )
+            .is_none()
+        {
+            let sold_back = account
+                .send_market_order(
+                    &buy.product_id,
+                    TradeSide::Sell,
+                    None,
+                    Some(size.to_string()),
+                )
+                .is_some();
+            println!(
+                "{:?} arbitrage sell on {} failed, sold back on {}: {}",
+                self.symbol, sell.product_id, buy.product_id, sold_back
+            );
+            return true;
+        }
+
+        let proceeds = size.to_f64().unwrap_or(0.0) * discrepancy.sell_price * (1.0 - taker_fee);
+        account.convert(discrepancy.sell_quote, discrepancy.buy_quote, proceeds);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::assert_close;
+
+    fn config() -> ArbitrageConfig {
+        ArbitrageConfig {
+            symbols: Vec::new(),
+            margin: DEFAULT_ARBITRAGE_MARGIN,
+            execute: false,
+            quote_amount: DEFAULT_ARBITRAGE_QUOTE_AMOUNT,
+            convert_fee: 0.0,
+        }
+    }
+
+    fn book(product_id: &str, bid: f64, ask: f64) -> QuoteBook {
+        QuoteBook {
+            product: serde_json::from_value(serde_json::json!({
+                "product_id": product_id,
+                "price": bid.to_string(),
+                "quote_min_size": "1",
+                "quote_max_size": "1000000",
+                "base_min_size": "0.001",
+                "base_max_size": "1000",
+                "base_increment": "0.001",
+                "quote_increment": "0.01",
+                "price_increment": "0.01",
+            }))
+            .unwrap(),
+            quote: Some(Quote {
+                bid,
+                ask,
+                time: Instant::now(),
+            }),
+        }
+    }
+
+    fn monitor(usd: (f64, f64), usdc: (f64, f64)) -> ArbitrageMonitor {
+        ArbitrageMonitor {
+            symbol: CoinSymbol::Eth,
+            config: config(),
+            usd: book("ETH-USD", usd.0, usd.1),
+            usdc: book("ETH-USDC", usdc.0, usdc.1),
+            last_log: None,
+            next_trade: None,
+        }
+    }
+
+    #[test]
+    fn buys_the_cheap_book_and_sells_the_rich_one() {
+        let discrepancy = monitor((99.9, 100.0), (102.0, 102.1))
+            .get_discrepancy(0.0, 0.0)
+            .unwrap();
+
+        assert_eq!(discrepancy.buy_quote, CoinSymbol::Usd);
+        assert_eq!(discrepancy.sell_quote, CoinSymbol::Usdc);
+        assert_eq!(discrepancy.buy_price, 100.0);
+        assert_eq!(discrepancy.sell_price, 102.0);
+        assert_close(discrepancy.edge, 0.02);
+
+        let discrepancy = monitor((102.0, 102.1), (99.9, 100.0))
+            .get_discrepancy(0.0, 0.0)
+            .unwrap();
+        assert_eq!(discrepancy.buy_quote, CoinSymbol::Usdc);
+        assert_close(discrepancy.edge, 0.02);
+    }
+
+    #[test]
+    fn edge_is_after_both_taker_fees_and_the_convert() {
+        let monitor = monitor((99.9, 100.0), (102.0, 102.1));
+
+        // 1.02 * 0.99 * 0.99 - 1, the 2% gap doesn't cover two 1% fees.
+        let edge = monitor.get_discrepancy(0.01, 0.0).unwrap().edge;
+        assert_close(edge, -0.000298);
+
+        // 1.02 * 0.99 - 1 with no taker fee.
+        let edge = monitor.get_discrepancy(0.0, 0.01).unwrap().edge;
+        assert_close(edge, 0.0098);
+
+        let edge = monitor.get_discrepancy(0.005, 0.001).unwrap().edge;
+        assert_close(edge, 1.02 * 0.995 * 0.995 * 0.999 - 1.0);
+    }
+
+    #[test]
+    fn needs_a_fresh_quote_on_both_books() {
+        let mut monitor = monitor((99.9, 100.0), (102.0, 102.1));
+        monitor.usdc.quote = None;
+        assert_eq!(monitor.get_discrepancy(0.0, 0.0), None);
+
+        let stale = Instant::now().checked_sub(QUOTE_MAX_AGE).unwrap();
+        monitor.usdc.quote = Some(Quote {
+            bid: 102.0,
+            ask: 102.1,
+            time: stale,
+        });
+        assert_eq!(monitor.get_discrepancy(0.0, 0.0), None);
+    }
+
+    #[test]
+    fn validates_the_config() {
+        assert!(config().validate().is_ok());
+
+        let bad = [
+            ArbitrageConfig {
+                margin: 0.0,
+                ..config()
+            },
+            ArbitrageConfig {
+                convert_fee: -0.001,
+                ..config()
+            },
+            ArbitrageConfig {
+                convert_fee: 1.0,
+                ..config()
+            },
+            ArbitrageConfig {
+                quote_amount: 0.0,
+                ..config()
+            },
+        ];
+        for config in bad {
+            assert!(config.validate().is_err(), "{:?}", config);
+        }
+    }
+}
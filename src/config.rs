@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{
+    arbitrage::ArbitrageConfig,
+    coin::CoinSymbol,
+    dca::DcaConfig,
+    grid::GridConfig,
+    market_maker::MarketMakerConfig,
+    pairs::PairConfig,
+    strategy::{
+        levels::LevelsConfig, pattern_filter::PatternFilterConfig, regime::RegimeFilterConfig,
+        StrategyConfig,
+    },
+    transform::TransformConfig,
+};
+
+const DEFAULT_CONFIG_PATH: &str = "config.json";
+
+// Per symbol settings, keyed by the coin ticker e.g. "BTC".
+// Symbols missing from the file use the default settings.
+//
+// {
+//     "symbols": {
+//         "XRP": { "strategy": { "type": "macd_ema", "fast": 12, "slow": 26, "signal": 9, "ema": 50 } }
+//     }
+// }
+#[derive(Debug, Default, Deserialize)]
+pub struct BotConfig {
+    #[serde(default)]
+    symbols: HashMap<String, SymbolConfig>,
+    // Spreads between two of the traded coins, run on their own thread.
+    #[serde(default)]
+    pairs: Vec<PairConfig>,
+    // Compares the USD and USDC books of the coins on their own thread.
+    #[serde(default)]
+    arbitrage: Option<ArbitrageConfig>,
+}
+
+// What the bot does with sell signals while it has no open long.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TradeMode {
+    // Sits in USDC.
+    #[default]
+    LongOnly,
+    // Buys a defensive asset, e.g. "PAXG", with the symbol's share of USDC.
+    Rotate {
+        asset: String,
+    },
+    // Shorts a derivatives product, e.g. "BTC-PERP-INTX".
+    Short {
+        product_id: String,
+    },
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct SymbolConfig {
+    #[serde(default)]
+    pub strategy: StrategyConfig,
+    // Holds back entries while adx is under the threshold.
+    #[serde(default)]
+    pub regime_filter: Option<RegimeFilterConfig>,
+    // Holds back entries until a bullish candlestick pattern confirms them.
+    #[serde(default)]
+    pub pattern_filter: Option<PatternFilterConfig>,
+    // Feeds the strategy heikin ashi or renko candles instead of the raw ones.
+    #[serde(default)]
+    pub transform: Option<TransformConfig>,
+    // Support and resistance used to skip entries under resistance and to
+    // place stops under support.
+    #[serde(default)]
+    pub levels: Option<LevelsConfig>,
+    #[serde(default)]
+    pub mode: TradeMode,
+    // Runs a limit order grid on the ticker in place of the strategy signals.
+    #[serde(default)]
+    pub grid: Option<GridConfig>,
+    // Scheduled buys that run alongside whatever else trades the coin.
+    #[serde(default)]
+    pub dca: Option<DcaConfig>,
+    // Quotes both sides of the book in place of the strategy signals.
+    #[serde(default)]
+    pub market_maker: Option<MarketMakerConfig>,
+}
+
+impl SymbolConfig {
+    fn validate(&self) -> Result<(), String> {
+        self.strategy.validate()?;
+        if let Some(transform) = &self.transform {
+            transform.validate()?;
+        }
+        if let Some(dca) = &self.dca {
+            dca.validate()?;
+        }
+        if let Some(grid) = &self.grid {
+            grid.validate()?;
+        }
+        if let Some(market_maker) = &self.market_maker {
+            market_maker.validate()?;
+        }
+        Ok(())
+    }
+}
+
+impl BotConfig {
+    // Reads the file at BOT_CONFIG, or config.json, falling back to defaults.
+    pub fn load() -> Self {
+        dotenv::dotenv().ok();
+        let path = std::env::var("BOT_CONFIG").unwrap_or(DEFAULT_CONFIG_PATH.to_string());
+
+        let config: BotConfig = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .unwrap_or_else(|e| panic!("Failed to parse config {}: {}", path, e)),
+            Err(_) => {
+                println!("No config found at {}, using defaults", path);
+                BotConfig::default()
+            }
+        };
+
+        if let Err(e) = config.validate() {
+            panic!("Invalid config {}: {}", path, e);
+        }
+        config
+    }
+
+    // Catches settings that parse fine but would break a thread at runtime.
+    fn validate(&self) -> Result<(), String> {
+        for (symbol, config) in self.symbols.iter() {
+            config
+                .validate()
+                .map_err(|e| format!("{}: {}", symbol, e))?;
+        }
+        if let Some(arbitrage) = &self.arbitrage {
+            arbitrage.validate()?;
+        }
+        for pair in self.pairs.iter() {
+            pair.validate()
+                .map_err(|e| format!("{}/{}: {}", pair.a, pair.b, e))?;
+        }
+        Ok(())
+    }
+
+    pub fn get_symbol_config(&self, symbol: CoinSymbol) -> SymbolConfig {
+        self.symbols
+            .get(&String::from(symbol))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn get_pairs(&self) -> Vec<PairConfig> {
+        self.pairs.clone()
+    }
+
+    pub fn get_arbitrage(&self) -> Option<ArbitrageConfig> {
+        self.arbitrage.clone()
+    }
+}
@@ -0,0 +1,287 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::model::event::HeartbeatEvent;
+
+// Heartbeats arrive every second, allow for a few to go missing.
+pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_MISSED_HEARTBEATS: u64 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceGap {
+    pub expected: u64,
+    pub received: u64,
+}
+
+impl fmt::Display for SequenceGap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Sequence gap, expected: {}, received: {}, missed: {}",
+            self.expected,
+            self.received,
+            self.received.saturating_sub(self.expected)
+        )
+    }
+}
+
+// Tracks the sequence_num of a single websocket connection. The number is
+// shared by every channel on the connection so any jump means a dropped message.
+#[derive(Debug, Default)]
+pub struct SequenceTracker {
+    last: Option<u64>,
+    gap_count: u64,
+}
+
+impl SequenceTracker {
+    pub fn new() -> Self {
+        SequenceTracker::default()
+    }
+
+    // A duplicate or late message doesn't move the count back, so it can't
+    // make the next message look like a gap.
+    pub fn check(&mut self, sequence_num: u64) -> Result<(), SequenceGap> {
+        let last = self.last;
+        self.last = Some(last.map_or(sequence_num, |last| last.max(sequence_num)));
+
+        match last {
+            Some(last) if sequence_num > last + 1 => {
+                self.gap_count += 1;
+                Err(SequenceGap {
+                    expected: last + 1,
+                    received: sequence_num,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // A new connection starts counting from zero again.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+
+    #[inline]
+    pub fn get_gap_count(&self) -> u64 {
+        self.gap_count
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeartbeatError {
+    Stale(Duration),
+    CounterJump { last: u64, received: u64 },
+}
+
+impl fmt::Display for HeartbeatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeartbeatError::Stale(age) => {
+                write!(f, "No heartbeat for {} seconds", age.as_secs())
+            }
+            HeartbeatError::CounterJump { last, received } => {
+                write!(f, "Heartbeat counter jumped from {} to {}", last, received)
+            }
+        }
+    }
+}
+
+// Watches the heartbeats channel so a half open connection that never
+// returns from a read is noticed and replaced. Times are passed in so the
+// timeouts can be tested without waiting on them.
+#[derive(Debug)]
+pub struct HeartbeatWatchdog {
+    connected_at: Instant,
+    last_heartbeat: Option<Instant>,
+    last_counter: Option<u64>,
+    last_server_time: Option<String>,
+}
+
+impl HeartbeatWatchdog {
+    pub fn new(now: Instant) -> Self {
+        HeartbeatWatchdog {
+            connected_at: now,
+            last_heartbeat: None,
+            last_counter: None,
+            last_server_time: None,
+        }
+    }
+
+    pub fn record(
+        &mut self,
+        heartbeat: &HeartbeatEvent,
+        now: Instant,
+    ) -> Result<(), HeartbeatError> {
+        let last_counter = self.last_counter;
+
+        self.last_heartbeat = Some(now);
+        self.last_counter = Some(heartbeat.heartbeat_counter);
+        self.last_server_time = Some(heartbeat.current_time.clone());
+
+        match last_counter {
+            Some(last) if heartbeat.heartbeat_counter > last + MAX_MISSED_HEARTBEATS => {
+                Err(HeartbeatError::CounterJump {
+                    last,
+                    received: heartbeat.heartbeat_counter,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check(&self, now: Instant) -> Result<(), HeartbeatError> {
+        let age = self.last_heartbeat_age(now);
+
+        if age > HEARTBEAT_TIMEOUT {
+            Err(HeartbeatError::Stale(age))
+        } else {
+            Ok(())
+        }
+    }
+
+    // Time since the last heartbeat, or since connecting if none has arrived yet.
+    pub fn last_heartbeat_age(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_heartbeat.unwrap_or(self.connected_at))
+    }
+
+    pub fn reset(&mut self, now: Instant) {
+        *self = HeartbeatWatchdog::new(now);
+    }
+}
+
+impl fmt::Display for HeartbeatWatchdog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Last Heartbeat Age: {}ms, Counter: {:?}, Server Time: {:?}",
+            self.last_heartbeat_age(Instant::now()).as_millis(),
+            self.last_counter,
+            self.last_server_time
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_order_messages_have_no_gap() {
+        let mut tracker = SequenceTracker::new();
+
+        for sequence_num in 0..5 {
+            assert_eq!(tracker.check(sequence_num), Ok(()));
+        }
+        assert_eq!(tracker.get_gap_count(), 0);
+    }
+
+    #[test]
+    fn reports_a_gap_once() {
+        let mut tracker = SequenceTracker::new();
+        tracker.check(3).unwrap();
+
+        assert_eq!(
+            tracker.check(6),
+            Err(SequenceGap {
+                expected: 4,
+                received: 6,
+            })
+        );
+        assert_eq!(tracker.check(7), Ok(()));
+        assert_eq!(tracker.get_gap_count(), 1);
+    }
+
+    #[test]
+    fn duplicate_or_old_messages_are_not_gaps() {
+        let mut tracker = SequenceTracker::new();
+        for sequence_num in 1..=3 {
+            tracker.check(sequence_num).unwrap();
+        }
+
+        assert_eq!(tracker.check(3), Ok(()));
+        assert_eq!(tracker.check(2), Ok(()));
+        // Carries on from 3 rather than the late 2.
+        assert_eq!(tracker.check(4), Ok(()));
+        assert_eq!(tracker.get_gap_count(), 0);
+    }
+
+    #[test]
+    fn reset_starts_a_new_connection() {
+        let mut tracker = SequenceTracker::new();
+        tracker.check(10).unwrap();
+        assert!(tracker.check(20).is_err());
+
+        tracker.reset();
+        assert_eq!(tracker.check(0), Ok(()));
+        assert_eq!(tracker.check(1), Ok(()));
+        // Gaps are counted over the life of the bot.
+        assert_eq!(tracker.get_gap_count(), 1);
+    }
+
+    fn heartbeat(counter: u64) -> HeartbeatEvent {
+        HeartbeatEvent {
+            current_time: format!("2023-06-23 20:31:{:02}", counter % 60),
+            heartbeat_counter: counter,
+        }
+    }
+
+    #[test]
+    fn healthy_until_the_timeout_passes() {
+        let start = Instant::now();
+        let mut watchdog = HeartbeatWatchdog::new(start);
+
+        // Counted from connecting until the first heartbeat.
+        assert_eq!(watchdog.check(start + HEARTBEAT_TIMEOUT), Ok(()));
+        assert!(watchdog.check(start + HEARTBEAT_TIMEOUT * 2).is_err());
+
+        let beat = start + Duration::from_secs(1);
+        assert_eq!(watchdog.record(&heartbeat(1), beat), Ok(()));
+        assert_eq!(watchdog.check(beat + HEARTBEAT_TIMEOUT), Ok(()));
+
+        let late = beat + HEARTBEAT_TIMEOUT + Duration::from_secs(1);
+        assert_eq!(
+            watchdog.check(late),
+            Err(HeartbeatError::Stale(Duration::from_secs(11)))
+        );
+    }
+
+    #[test]
+    fn trips_when_too_many_heartbeats_are_missed() {
+        let now = Instant::now();
+        let mut watchdog = HeartbeatWatchdog::new(now);
+        watchdog.record(&heartbeat(1), now).unwrap();
+
+        // Missing up to MAX_MISSED_HEARTBEATS is allowed.
+        assert_eq!(watchdog.record(&heartbeat(2), now), Ok(()));
+        assert_eq!(
+            watchdog.record(&heartbeat(2 + MAX_MISSED_HEARTBEATS), now),
+            Ok(())
+        );
+        assert_eq!(
+            watchdog.record(&heartbeat(6 + MAX_MISSED_HEARTBEATS), now),
+            Err(HeartbeatError::CounterJump {
+                last: 5,
+                received: 9,
+            })
+        );
+    }
+
+    #[test]
+    fn recovers_after_a_heartbeat() {
+        let start = Instant::now();
+        let mut watchdog = HeartbeatWatchdog::new(start);
+        let stale = start + HEARTBEAT_TIMEOUT * 2;
+        assert!(watchdog.check(stale).is_err());
+
+        watchdog.record(&heartbeat(1), stale).unwrap();
+        assert_eq!(watchdog.check(stale), Ok(()));
+
+        let reconnected = stale + HEARTBEAT_TIMEOUT * 2;
+        assert!(watchdog.check(reconnected).is_err());
+        watchdog.reset(reconnected);
+        assert_eq!(watchdog.check(reconnected), Ok(()));
+        assert_eq!(watchdog.last_counter, None);
+    }
+}
@@ -0,0 +1,303 @@
+use serde::Deserialize;
+
+use crate::{
+    account::BotAccount,
+    coin::CoinSymbol,
+    indicators::{ema::Ema, rsi::Rsi, Indicator},
+    model::event::Candlestick,
+};
+
+const SECONDS_PER_HOUR: i64 = 3_600;
+
+const DEFAULT_DCA_INTERVAL_HOURS: i64 = 24;
+const DEFAULT_MIN_MULTIPLIER: f64 = 0.5;
+const DEFAULT_MAX_MULTIPLIER: f64 = 2.0;
+const DEFAULT_RSI_PERIOD: usize = 14;
+const DEFAULT_RSI_OVERSOLD: f64 = 30.0;
+const DEFAULT_RSI_OVERBOUGHT: f64 = 70.0;
+const DEFAULT_EMA_PERIOD: usize = 50;
+const DEFAULT_DISTANCE_SCALE: f64 = 10.0;
+
+fn default_interval_hours() -> i64 {
+    DEFAULT_DCA_INTERVAL_HOURS
+}
+
+fn default_min_multiplier() -> f64 {
+    DEFAULT_MIN_MULTIPLIER
+}
+
+fn default_max_multiplier() -> f64 {
+    DEFAULT_MAX_MULTIPLIER
+}
+
+fn default_rsi_period() -> usize {
+    DEFAULT_RSI_PERIOD
+}
+
+fn default_rsi_oversold() -> f64 {
+    DEFAULT_RSI_OVERSOLD
+}
+
+fn default_rsi_overbought() -> f64 {
+    DEFAULT_RSI_OVERBOUGHT
+}
+
+fn default_ema_period() -> usize {
+    DEFAULT_EMA_PERIOD
+}
+
+fn default_distance_scale() -> f64 {
+    DEFAULT_DISTANCE_SCALE
+}
+
+// Buys quote_amount USDC of the coin every interval_hours, scaled between
+// min_multiplier and max_multiplier when a weighting is set.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DcaConfig {
+    pub quote_amount: f64,
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: i64,
+    #[serde(default)]
+    pub weighting: Option<DcaWeighting>,
+    #[serde(default = "default_min_multiplier")]
+    pub min_multiplier: f64,
+    #[serde(default = "default_max_multiplier")]
+    pub max_multiplier: f64,
+}
+
+impl DcaConfig {
+    // The multiplier clamps between min and max and the rsi weight divides by
+    // overbought - oversold, so both ranges have to be the right way round.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.quote_amount <= 0.0 {
+            return Err(format!(
+                "dca quote_amount must be positive, got {}",
+                self.quote_amount
+            ));
+        }
+
+        if self.min_multiplier < 0.0 || self.min_multiplier > self.max_multiplier {
+            return Err(format!(
+                "dca multipliers need 0 <= min_multiplier <= max_multiplier, got {} and {}",
+                self.min_multiplier, self.max_multiplier
+            ));
+        }
+
+        match self.weighting {
+            Some(DcaWeighting::Rsi {
+                oversold,
+                overbought,
+                ..
+            }) if overbought <= oversold => Err(format!(
+                "dca rsi overbought {} must be over oversold {}",
+                overbought, oversold
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DcaWeighting {
+    // Buys the most at or under oversold and the least at or over overbought.
+    Rsi {
+        #[serde(default = "default_rsi_period")]
+        period: usize,
+        #[serde(default = "default_rsi_oversold")]
+        oversold: f64,
+        #[serde(default = "default_rsi_overbought")]
+        overbought: f64,
+    },
+    // Scales the buy by 1 + scale times the fraction price is under the ema.
+    EmaDistance {
+        #[serde(default = "default_ema_period")]
+        period: usize,
+        #[serde(default = "default_distance_scale")]
+        scale: f64,
+    },
+}
+
+#[derive(Debug)]
+enum Weight {
+    Flat,
+    Rsi {
+        rsi: Rsi,
+        oversold: f64,
+        overbought: f64,
+    },
+    EmaDistance {
+        ema: Ema,
+        scale: f64,
+    },
+}
+
+// Runs alongside the strategy on the closed candles, it only ever buys.
+#[derive(Debug)]
+pub struct Dca {
+    config: DcaConfig,
+    weight: Weight,
+    last_close: f64,
+    last_start: i64,
+}
+
+impl Dca {
+    pub fn new(config: DcaConfig) -> Self {
+        let weight = match config.weighting {
+            None => Weight::Flat,
+            Some(DcaWeighting::Rsi {
+                period,
+                oversold,
+                overbought,
+            }) => Weight::Rsi {
+                rsi: Rsi::new(period),
+                oversold,
+                overbought,
+            },
+            Some(DcaWeighting::EmaDistance { period, scale }) => Weight::EmaDistance {
+                ema: Ema::new(period),
+                scale,
+            },
+        };
+
+        Dca {
+            config,
+            weight,
+            last_close: 0.0,
+            last_start: 0,
+        }
+    }
+
+    pub fn on_candle(&mut self, candle: &Candlestick) {
+        match &mut self.weight {
+            Weight::Flat => (),
+            Weight::Rsi { rsi, .. } => {
+                rsi.on_candle(candle);
+            }
+            Weight::EmaDistance { ema, .. } => {
+                ema.on_candle(candle);
+            }
+        }
+
+        self.last_close = candle.close;
+        self.last_start = candle.start;
+    }
+
+    pub fn reset(&mut self) {
+        match &mut self.weight {
+            Weight::Flat => (),
+            Weight::Rsi { rsi, .. } => rsi.reset(),
+            Weight::EmaDistance { ema, .. } => ema.reset(),
+        }
+    }
+
+    // Flat until the weighting indicator has warmed up.
+    fn get_multiplier(&self) -> f64 {
+        let (min, max) = (self.config.min_multiplier, self.config.max_multiplier);
+
+        match &self.weight {
+            Weight::Flat => 1.0,
+            Weight::Rsi {
+                rsi,
+                oversold,
+                overbought,
+            } => rsi.get_value().map_or(1.0, |rsi| {
+                let weight = ((overbought - rsi) / (overbought - oversold)).clamp(0.0, 1.0);
+                min + weight * (max - min)
+            }),
+            Weight::EmaDistance { ema, scale } => ema.get_value().map_or(1.0, |ema| {
+                let distance = (ema - self.last_close) / ema;
+                (1.0 + distance * scale).clamp(min, max)
+            }),
+        }
+    }
+
+    // Buys once per interval, the schedule is lined up on multiples of the
+    // interval in candle time so a restart doesn't buy again early.
+    pub fn on_schedule(&self, symbol: CoinSymbol, account: &mut BotAccount) {
+        if self.last_close <= 0.0 {
+            return;
+        }
+
+        let interval = self.config.interval_hours.max(1) * SECONDS_PER_HOUR;
+        if self.last_start - account.get_dca_last_buy() < interval {
+            return;
+        }
+
+        let multiplier = self.get_multiplier();
+        println!("{:?} dca buy, multiplier: {:.2}", symbol, multiplier);
+
+        account.create_dca_order(
+            symbol,
+            self.config.quote_amount * multiplier,
+            self.last_close,
+            self.last_start - self.last_start.rem_euclid(interval),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::ohlcv;
+
+    fn config(weighting: Option<DcaWeighting>) -> DcaConfig {
+        DcaConfig {
+            quote_amount: 10.0,
+            interval_hours: DEFAULT_DCA_INTERVAL_HOURS,
+            weighting,
+            min_multiplier: DEFAULT_MIN_MULTIPLIER,
+            max_multiplier: DEFAULT_MAX_MULTIPLIER,
+        }
+    }
+
+    fn rsi_weighting(oversold: f64, overbought: f64) -> Option<DcaWeighting> {
+        Some(DcaWeighting::Rsi {
+            period: DEFAULT_RSI_PERIOD,
+            oversold,
+            overbought,
+        })
+    }
+
+    #[test]
+    fn accepts_the_defaults() {
+        assert!(config(None).validate().is_ok());
+        assert!(config(rsi_weighting(30.0, 70.0)).validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_bad_ranges() {
+        let mut reversed = config(None);
+        reversed.min_multiplier = 3.0;
+        assert!(reversed.validate().is_err());
+
+        let mut empty = config(None);
+        empty.quote_amount = 0.0;
+        assert!(empty.validate().is_err());
+
+        assert!(config(rsi_weighting(50.0, 50.0)).validate().is_err());
+        assert!(config(rsi_weighting(70.0, 30.0)).validate().is_err());
+    }
+
+    #[test]
+    fn ema_distance_scales_between_the_multipliers() {
+        let mut dca = Dca::new(config(Some(DcaWeighting::EmaDistance {
+            period: 1,
+            scale: 10.0,
+        })));
+        assert_eq!(dca.get_multiplier(), 1.0);
+
+        // A one candle ema sits on the close, so the buy is flat.
+        dca.on_candle(&ohlcv(100.0, 100.0, 100.0, 100.0, 0.0));
+        assert_eq!(dca.get_multiplier(), 1.0);
+
+        dca.last_close = 95.0;
+        assert!((dca.get_multiplier() - 1.5).abs() < 1e-9);
+
+        dca.last_close = 50.0;
+        assert_eq!(dca.get_multiplier(), DEFAULT_MAX_MULTIPLIER);
+
+        dca.last_close = 200.0;
+        assert_eq!(dca.get_multiplier(), DEFAULT_MIN_MULTIPLIER);
+    }
+}
@@ -0,0 +1,548 @@
+use std::time::{Duration, Instant};
+
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::Deserialize;
+
+use crate::{
+    account::BotAccount,
+    coin::CoinSymbol,
+    model::{account::Product, OrderStatus, TradeSide},
+    order_manager::{LimitOrder, OrderManager, Settlement},
+};
+
+// Fills are polled over rest so keep it well inside the rate limits.
+const GRID_UPDATE_INTERVAL: Duration = Duration::from_secs(10);
+
+const DEFAULT_GRID_LEVELS: usize = 10;
+
+fn default_levels() -> usize {
+    DEFAULT_GRID_LEVELS
+}
+
+fn default_post_only() -> bool {
+    true
+}
+
+// Splits lower..upper into `levels` evenly spaced steps, `order_size` is the
+// quote amount each level buys with.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GridConfig {
+    pub lower: f64,
+    pub upper: f64,
+    #[serde(default = "default_levels")]
+    pub levels: usize,
+    pub order_size: f64,
+    #[serde(default = "default_post_only")]
+    pub post_only: bool,
+}
+
+impl GridConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.levels == 0 {
+            return Err("grid needs at least one level".to_string());
+        }
+
+        if self.lower <= 0.0 || self.lower >= self.upper {
+            return Err(format!(
+                "grid range needs 0 < lower < upper, got {} - {}",
+                self.lower, self.upper
+            ));
+        }
+
+        if self.order_size <= 0.0 {
+            return Err(format!(
+                "grid order_size must be positive, got {}",
+                self.order_size
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LevelState {
+    Idle,
+    Buying,
+    Bought,
+    Selling,
+}
+
+// Buys at one grid line and sells at the one above it.
+#[derive(Debug)]
+struct GridLevel {
+    buy_price: Decimal,
+    sell_price: Decimal,
+    size: Decimal,
+    // Bought and not sold yet, what the sell is placed for. Less than size
+    // after a buy that was cancelled part way through.
+    held: Decimal,
+    state: LevelState,
+    pnl: f64,
+    round_trips: u32,
+}
+
+impl GridLevel {
+    // The side, price and size the level is waiting to place, None when it
+    // has one resting or the order would cross the market.
+    fn next_order(&self, price: Decimal) -> Option<(TradeSide, Decimal, Decimal)> {
+        match self.state {
+            LevelState::Idle if self.buy_price < price => {
+                Some((TradeSide::Buy, self.buy_price, self.size))
+            }
+            LevelState::Bought if self.sell_price > price => {
+                Some((TradeSide::Sell, self.sell_price, self.held))
+            }
+            _ => None,
+        }
+    }
+
+    fn on_placed(&mut self, side: TradeSide) {
+        self.state = match side {
+            TradeSide::Buy => LevelState::Buying,
+            TradeSide::Sell => LevelState::Selling,
+        };
+    }
+
+    // Also takes the filled part of an order cancelled part way through, a
+    // partial buy is sold back at the level above and a partial sell leaves
+    // the rest to sell again.
+    fn on_filled(&mut self, side: TradeSide, filled: Decimal, maker_fee: f64) {
+        match side {
+            TradeSide::Buy => {
+                self.held += filled;
+                self.state = LevelState::Bought;
+            }
+            TradeSide::Sell => {
+                let filled = filled.min(self.held);
+                self.held -= filled;
+
+                let buy = self.buy_price.to_f64().unwrap_or(0.0);
+                let sell = self.sell_price.to_f64().unwrap_or(0.0);
+                let size = filled.to_f64().unwrap_or(0.0);
+                self.pnl += (sell - buy) * size - maker_fee * (buy + sell) * size;
+
+                if self.held.is_zero() {
+                    self.round_trips += 1;
+                    self.state = LevelState::Idle;
+                } else {
+                    self.state = LevelState::Bought;
+                }
+            }
+        }
+    }
+
+    // An order that settled without filling puts the level back where it
+    // was, so the next replenish places it again.
+    fn on_unfilled(&mut self, side: TradeSide) {
+        self.state = match (side, self.state) {
+            (TradeSide::Buy, LevelState::Buying) => LevelState::Idle,
+            (TradeSide::Sell, LevelState::Selling) => LevelState::Bought,
+            (_, state) => state,
+        };
+    }
+}
+
+#[derive(Debug)]
+pub struct Grid {
+    symbol: CoinSymbol,
+    config: GridConfig,
+    orders: OrderManager,
+    levels: Vec<GridLevel>,
+    maker_fee: f64,
+    active: bool,
+    last_update: Option<Instant>,
+}
+
+impl Grid {
+    pub fn new(symbol: CoinSymbol, config: GridConfig, product: Product, maker_fee: f64) -> Self {
+        let step = (config.upper - config.lower) / config.levels as f64;
+        let mut levels = Vec::with_capacity(config.levels);
+
+        for i in 0..config.levels {
+            let buy = config.lower + step * i as f64;
+            let (buy_price, sell_price) =
+                match (product.round_price(buy), product.round_price(buy + step)) {
+                    (Some(buy_price), Some(sell_price)) if buy_price < sell_price => {
+                        (buy_price, sell_price)
+                    }
+                    _ => continue,
+                };
+
+            let size = match product.round_base(config.order_size / buy) {
+                Some(size) if size.to_f64().unwrap_or(0.0) >= product.base_min_size => size,
+                _ => {
+                    println!(
+                        "{:?} grid level at {} is under the minimum size, skipping",
+                        symbol, buy_price
+                    );
+                    continue;
+                }
+            };
+
+            levels.push(GridLevel {
+                buy_price,
+                sell_price,
+                size,
+                held: Decimal::ZERO,
+                state: LevelState::Idle,
+                pnl: 0.0,
+                round_trips: 0,
+            });
+        }
+
+        let active = !levels.is_empty();
+
+        Grid {
+            symbol,
+            config,
+            orders: OrderManager::new(symbol, product),
+            levels,
+            maker_fee,
+            active,
+            last_update: None,
+        }
+    }
+
+    // Runs on every ticker, the range check is immediate but fills and
+    // replacement orders only go out every GRID_UPDATE_INTERVAL.
+    pub fn on_price(&mut self, price: f64, account: &mut BotAccount) {
+        if !self.active {
+            return;
+        }
+
+        if price < self.config.lower || price > self.config.upper {
+            println!(
+                "{:?} price {} left the grid range {} - {}",
+                self.symbol, price, self.config.lower, self.config.upper
+            );
+            self.stop(account);
+            return;
+        }
+
+        if self
+            .last_update
+            .is_some_and(|last| last.elapsed() < GRID_UPDATE_INTERVAL)
+        {
+            return;
+        }
+        self.last_update = Some(Instant::now());
+
+        for settlement in self.orders.poll_fills(account) {
+            self.on_settled(settlement);
+        }
+
+        self.replenish(price, account);
+    }
+
+    fn on_settled(&mut self, settlement: Settlement) {
+        let Settlement {
+            order,
+            status,
+            filled_size,
+            ..
+        } = settlement;
+        let level = match self.levels.get_mut(order.tag) {
+            Some(level) => level,
+            None => return,
+        };
+
+        // A full fill goes by the order so the level's sizes stay exact.
+        let filled = match status {
+            OrderStatus::Filled => order.size,
+            _ => self
+                .orders
+                .get_product()
+                .round_base(filled_size)
+                .unwrap_or_default(),
+        };
+
+        // Too little of a cancelled buy to sell back is left in the wallet.
+        if filled.is_zero()
+            || (order.side == TradeSide::Buy
+                && filled.to_f64().unwrap_or(0.0) < self.orders.get_product().base_min_size)
+        {
+            level.on_unfilled(order.side);
+            return;
+        }
+
+        level.on_filled(order.side, filled, self.maker_fee);
+        println!(
+            "{:?} grid {} {:?} {} at {}, level pnl: {:.4}",
+            self.symbol, order.side, status, filled, order.price, level.pnl
+        );
+    }
+
+    // Buys rest under the price and sells above it, a level waits when its
+    // order would cross the market.
+    fn replenish(&mut self, price: f64, account: &mut BotAccount) {
+        let price = match Decimal::try_from(price) {
+            Ok(price) => price,
+            Err(_) => return,
+        };
+
+        for (tag, level) in self.levels.iter_mut().enumerate() {
+            let (side, limit_price, size) = match level.next_order(price) {
+                Some(order) => order,
+                None => continue,
+            };
+
+            let order = LimitOrder {
+                side,
+                price: limit_price,
+                size,
+                tag,
+            };
+
+            if self
+                .orders
+                .place_limit(account, order, self.config.post_only)
+            {
+                level.on_placed(side);
+            }
+        }
+    }
+
+    // Cancels every resting order and prints how each level did.
+    pub fn stop(&mut self, account: &mut BotAccount) {
+        if !self.active {
+            return;
+        }
+        self.active = false;
+        self.orders.cancel_all(account);
+
+        println!("{:?} grid stopped", self.symbol);
+        for level in self.levels.iter() {
+            println!(
+                "{:?} grid {} - {}: round trips: {}, pnl: {:.4}, holding: {}",
+                self.symbol,
+                level.buy_price,
+                level.sell_price,
+                level.round_trips,
+                level.pnl,
+                matches!(level.state, LevelState::Bought | LevelState::Selling)
+            );
+        }
+        println!(
+            "{:?} grid total pnl: {:.4}",
+            self.symbol,
+            self.levels.iter().map(|level| level.pnl).sum::<f64>()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product() -> Product {
+        serde_json::from_value(serde_json::json!({
+            "product_id": "XRP-USDC",
+            "price": "0.5",
+            "quote_min_size": "1",
+            "quote_max_size": "1000000",
+            "base_min_size": "1",
+            "base_max_size": "1000000",
+            "base_increment": "0.1",
+            "quote_increment": "0.01",
+            "price_increment": "0.0001",
+        }))
+        .unwrap()
+    }
+
+    fn config() -> GridConfig {
+        GridConfig {
+            lower: 0.4,
+            upper: 0.6,
+            levels: 4,
+            order_size: 10.0,
+            post_only: true,
+        }
+    }
+
+    // A level's order settled with `filled` of its 25 coins.
+    fn settlement(side: TradeSide, status: OrderStatus, filled: f64) -> Settlement {
+        Settlement {
+            order: LimitOrder {
+                side,
+                price: Decimal::new(4, 1),
+                size: Decimal::new(25, 0),
+                tag: 0,
+            },
+            status,
+            filled_size: filled,
+            average_price: 0.4,
+        }
+    }
+
+    fn level() -> GridLevel {
+        GridLevel {
+            buy_price: Decimal::new(10, 0),
+            sell_price: Decimal::new(11, 0),
+            size: Decimal::new(2, 0),
+            held: Decimal::ZERO,
+            state: LevelState::Idle,
+            pnl: 0.0,
+            round_trips: 0,
+        }
+    }
+
+    #[test]
+    fn builds_evenly_spaced_levels() {
+        let grid = Grid::new(CoinSymbol::Xrp, config(), product(), 0.0);
+
+        let prices: Vec<(String, String)> = grid
+            .levels
+            .iter()
+            .map(|level| (level.buy_price.to_string(), level.sell_price.to_string()))
+            .collect();
+        assert_eq!(
+            prices,
+            [
+                ("0.4", "0.45"),
+                ("0.45", "0.5"),
+                ("0.5", "0.55"),
+                ("0.55", "0.6")
+            ]
+            .map(|(buy, sell)| (buy.to_string(), sell.to_string()))
+        );
+        assert_eq!(grid.levels[0].size.to_string(), "25");
+    }
+
+    #[test]
+    fn only_places_orders_that_rest() {
+        let mut level = level();
+        assert_eq!(level.next_order(Decimal::new(95, 1)), None);
+        assert_eq!(
+            level.next_order(Decimal::new(105, 1)),
+            Some((TradeSide::Buy, Decimal::new(10, 0), Decimal::new(2, 0)))
+        );
+
+        level.state = LevelState::Bought;
+        level.held = Decimal::new(2, 0);
+        assert_eq!(level.next_order(Decimal::new(115, 1)), None);
+        assert_eq!(
+            level.next_order(Decimal::new(105, 1)),
+            Some((TradeSide::Sell, Decimal::new(11, 0), Decimal::new(2, 0)))
+        );
+
+        for state in [LevelState::Buying, LevelState::Selling] {
+            level.state = state;
+            assert_eq!(level.next_order(Decimal::new(105, 1)), None);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut level = level();
+
+        level.on_placed(TradeSide::Buy);
+        assert_eq!(level.state, LevelState::Buying);
+        level.on_filled(TradeSide::Buy, Decimal::new(2, 0), 0.01);
+        assert_eq!(level.state, LevelState::Bought);
+
+        level.on_placed(TradeSide::Sell);
+        assert_eq!(level.state, LevelState::Selling);
+        level.on_filled(TradeSide::Sell, Decimal::new(2, 0), 0.01);
+        assert_eq!(level.state, LevelState::Idle);
+
+        // 1 a coin on 2 coins, less 1% of the 20 bought and 22 sold.
+        assert_eq!(level.round_trips, 1);
+        assert!((level.pnl - 1.58).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unfilled_orders_put_the_level_back() {
+        let mut level = level();
+
+        level.on_placed(TradeSide::Buy);
+        level.on_unfilled(TradeSide::Buy);
+        assert_eq!(level.state, LevelState::Idle);
+
+        level.state = LevelState::Bought;
+        level.on_placed(TradeSide::Sell);
+        level.on_unfilled(TradeSide::Sell);
+        assert_eq!(level.state, LevelState::Bought);
+        assert_eq!(level.round_trips, 0);
+    }
+
+    #[test]
+    fn stale_unfilled_order_leaves_the_state() {
+        let mut level = level();
+        level.state = LevelState::Bought;
+
+        level.on_unfilled(TradeSide::Buy);
+        assert_eq!(level.state, LevelState::Bought);
+    }
+
+    #[test]
+    fn partially_filled_cancelled_buy_sells_what_it_got() {
+        let mut grid = Grid::new(CoinSymbol::Xrp, config(), product(), 0.0);
+        grid.levels[0].on_placed(TradeSide::Buy);
+
+        grid.on_settled(settlement(TradeSide::Buy, OrderStatus::Cancelled, 10.0));
+        assert_eq!(grid.levels[0].state, LevelState::Bought);
+        assert_eq!(
+            grid.levels[0].next_order(Decimal::new(42, 2)),
+            Some((TradeSide::Sell, Decimal::new(45, 2), Decimal::new(10, 0)))
+        );
+    }
+
+    #[test]
+    fn cancelled_buy_under_the_minimum_is_dropped() {
+        let mut grid = Grid::new(CoinSymbol::Xrp, config(), product(), 0.0);
+        grid.levels[0].on_placed(TradeSide::Buy);
+
+        grid.on_settled(settlement(TradeSide::Buy, OrderStatus::Cancelled, 0.5));
+        assert_eq!(grid.levels[0].state, LevelState::Idle);
+        assert!(grid.levels[0].held.is_zero());
+    }
+
+    #[test]
+    fn partially_filled_cancelled_sell_sells_the_rest() {
+        let mut level = level();
+        level.on_filled(TradeSide::Buy, Decimal::new(2, 0), 0.0);
+        level.on_placed(TradeSide::Sell);
+
+        level.on_filled(TradeSide::Sell, Decimal::new(5, 1), 0.0);
+        assert_eq!(level.state, LevelState::Bought);
+        assert_eq!(level.round_trips, 0);
+        assert!((level.pnl - 0.5).abs() < 1e-9);
+        assert_eq!(
+            level.next_order(Decimal::new(105, 1)),
+            Some((TradeSide::Sell, Decimal::new(11, 0), Decimal::new(15, 1)))
+        );
+
+        level.on_filled(TradeSide::Sell, Decimal::new(15, 1), 0.0);
+        assert_eq!(level.state, LevelState::Idle);
+        assert_eq!(level.round_trips, 1);
+        assert!((level.pnl - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn validates_the_config() {
+        assert!(config().validate().is_ok());
+
+        let bad = [
+            GridConfig {
+                levels: 0,
+                ..config()
+            },
+            GridConfig {
+                lower: 0.6,
+                upper: 0.4,
+                ..config()
+            },
+            GridConfig {
+                lower: 0.0,
+                ..config()
+            },
+            GridConfig {
+                order_size: 0.0,
+                ..config()
+            },
+        ];
+        for config in bad {
+            assert!(config.validate().is_err(), "{:?}", config);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
+// Under this the market is treated as ranging rather than trending.
+pub const ADX_THRESHOLD: f64 = 20.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdxValue {
+    pub adx: f64,
+    pub plus_di: f64,
+    pub minus_di: f64,
+}
+
+// Average directional index with the +DI and -DI lines, all wilder smoothed.
+#[derive(Debug)]
+pub struct Adx {
+    period: usize,
+    prev: Option<(f64, f64, f64)>,
+    // Sums until `period` moves are seen, then the wilder smoothed values.
+    tr: f64,
+    plus_dm: f64,
+    minus_dm: f64,
+    moves: usize,
+    dx_sum: f64,
+    dx_count: usize,
+    current: Option<AdxValue>,
+}
+
+impl Adx {
+    pub fn new(period: usize) -> Self {
+        Adx {
+            period,
+            prev: None,
+            tr: 0.0,
+            plus_dm: 0.0,
+            minus_dm: 0.0,
+            moves: 0,
+            dx_sum: 0.0,
+            dx_count: 0,
+            current: None,
+        }
+    }
+}
+
+impl Indicator for Adx {
+    type Output = AdxValue;
+
+    // One candle to start from, `period` moves for the first DI and
+    // `period` DX values for the first ADX.
+    fn warmup_period(&self) -> usize {
+        self.period * 2
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<AdxValue> {
+        let (prev_high, prev_low, prev_close) = match self.prev {
+            Some(prev) => prev,
+            None => {
+                self.prev = Some((candle.high, candle.low, candle.close));
+                return None;
+            }
+        };
+        self.prev = Some((candle.high, candle.low, candle.close));
+
+        let up_move = candle.high - prev_high;
+        let down_move = prev_low - candle.low;
+        let plus_dm = if up_move > down_move && up_move > 0.0 {
+            up_move
+        } else {
+            0.0
+        };
+        let minus_dm = if down_move > up_move && down_move > 0.0 {
+            down_move
+        } else {
+            0.0
+        };
+        let tr = (candle.high - candle.low)
+            .max((candle.high - prev_close).abs())
+            .max((candle.low - prev_close).abs());
+
+        let period = self.period as f64;
+        self.moves += 1;
+
+        if self.moves <= self.period {
+            self.tr += tr;
+            self.plus_dm += plus_dm;
+            self.minus_dm += minus_dm;
+
+            if self.moves < self.period {
+                return None;
+            }
+        } else {
+            self.tr = self.tr - self.tr / period + tr;
+            self.plus_dm = self.plus_dm - self.plus_dm / period + plus_dm;
+            self.minus_dm = self.minus_dm - self.minus_dm / period + minus_dm;
+        }
+
+        let (plus_di, minus_di) = if self.tr > 0.0 {
+            (
+                100.0 * self.plus_dm / self.tr,
+                100.0 * self.minus_dm / self.tr,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+        let di_sum = plus_di + minus_di;
+        let dx = if di_sum > 0.0 {
+            100.0 * (plus_di - minus_di).abs() / di_sum
+        } else {
+            0.0
+        };
+
+        let adx = match self.current {
+            Some(value) => (value.adx * (period - 1.0) + dx) / period,
+            None => {
+                self.dx_sum += dx;
+                self.dx_count += 1;
+
+                if self.dx_count < self.period {
+                    return None;
+                }
+                self.dx_sum / period
+            }
+        };
+
+        self.current = Some(AdxValue {
+            adx,
+            plus_di,
+            minus_di,
+        });
+        self.current
+    }
+
+    fn get_value(&self) -> Option<AdxValue> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        *self = Adx::new(self.period);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::{assert_close, assert_resets, ohlcv};
+
+    #[test]
+    fn matches_wilder() {
+        let mut adx = Adx::new(2);
+        let candles = [
+            ohlcv(9.0, 10.0, 8.0, 9.0, 0.0),
+            ohlcv(9.0, 12.0, 9.0, 11.0, 0.0),
+            ohlcv(11.0, 13.0, 10.0, 12.0, 0.0),
+        ];
+        for candle in candles.iter() {
+            assert_eq!(adx.on_candle(candle), None);
+        }
+
+        // Smoothed tr 6, +dm 1.5, -dm 1, so dx 20 against the first 100.
+        let value = adx.on_candle(&ohlcv(12.0, 12.0, 9.0, 10.0, 0.0)).unwrap();
+        assert_close(value.plus_di, 25.0);
+        assert_close(value.minus_di, 50.0 / 3.0);
+        assert_close(value.adx, 60.0);
+
+        // Smoothed tr 7, +dm 0.75, -dm 2.5, dx 700 / 13.
+        let value = adx.on_candle(&ohlcv(10.0, 11.0, 7.0, 8.0, 0.0)).unwrap();
+        assert_close(value.plus_di, 75.0 / 7.0);
+        assert_close(value.minus_di, 250.0 / 7.0);
+        assert_close(value.adx, (60.0 + 700.0 / 13.0) / 2.0);
+    }
+
+    #[test]
+    fn warms_up_in_two_periods() {
+        let mut adx = Adx::new(3);
+        let mut readings = 0;
+
+        for i in 0..adx.warmup_period() {
+            let price = 10.0 + i as f64;
+            readings += adx
+                .on_candle(&ohlcv(price, price + 1.0, price - 1.0, price, 0.0))
+                .map_or(0, |_| 1);
+        }
+
+        assert_eq!(readings, 1);
+    }
+
+    #[test]
+    fn reset_starts_over() {
+        let candles: Vec<_> = [10.0, 12.0, 11.0, 9.0, 13.0, 14.0]
+            .iter()
+            .map(|&close| ohlcv(close, close + 1.0, close - 2.0, close, 0.0))
+            .collect();
+        assert_resets(&mut Adx::new(2), &candles);
+    }
+}
@@ -1,3 +1,7 @@
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
 #[derive(Debug)]
 pub struct Atr {
     period: usize,
@@ -49,9 +53,25 @@ impl Atr {
 
         hl.max(hc.max(lc))
     }
+}
+
+impl Indicator for Atr {
+    type Output = f64;
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
 
-    #[inline]
-    pub fn get_atr(&self) -> Option<f64> {
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<f64> {
+        self.update(candle.high, candle.low, candle.close);
         self.atr_value
     }
+
+    fn get_value(&self) -> Option<f64> {
+        self.atr_value
+    }
+
+    fn reset(&mut self) {
+        *self = Atr::new(self.period);
+    }
 }
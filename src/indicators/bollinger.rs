@@ -0,0 +1,146 @@
+use std::collections::VecDeque;
+
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BollingerValue {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+    // Position of the close within the bands, 0 at the lower and 1 at the upper band.
+    pub percent_b: f64,
+    // Band width relative to the middle band.
+    pub bandwidth: f64,
+}
+
+// Sma of the close plus and minus `multiplier` standard deviations.
+#[derive(Debug)]
+pub struct Bollinger {
+    period: usize,
+    multiplier: f64,
+    values: VecDeque<f64>,
+    sum: f64,
+    sum_squares: f64,
+    current: Option<BollingerValue>,
+}
+
+impl Bollinger {
+    pub fn new(period: usize, multiplier: f64) -> Self {
+        Bollinger {
+            period,
+            multiplier,
+            values: VecDeque::with_capacity(period),
+            sum: 0.0,
+            sum_squares: 0.0,
+            current: None,
+        }
+    }
+
+    pub fn update(&mut self, close: f64) -> Option<BollingerValue> {
+        self.values.push_back(close);
+        self.sum += close;
+        self.sum_squares += close * close;
+
+        if self.values.len() > self.period {
+            let old = self.values.pop_front().unwrap_or_default();
+            self.sum -= old;
+            self.sum_squares -= old * old;
+        }
+
+        if self.values.len() < self.period {
+            return None;
+        }
+
+        let period = self.period as f64;
+        let middle = self.sum / period;
+        // Rounding can leave a tiny negative variance on a flat series.
+        let std_dev = (self.sum_squares / period - middle * middle)
+            .max(0.0)
+            .sqrt();
+        let upper = middle + self.multiplier * std_dev;
+        let lower = middle - self.multiplier * std_dev;
+
+        let percent_b = if upper > lower {
+            (close - lower) / (upper - lower)
+        } else {
+            0.5
+        };
+        let bandwidth = if middle != 0.0 {
+            (upper - lower) / middle
+        } else {
+            0.0
+        };
+
+        self.current = Some(BollingerValue {
+            upper,
+            middle,
+            lower,
+            percent_b,
+            bandwidth,
+        });
+        self.current
+    }
+}
+
+impl Indicator for Bollinger {
+    type Output = BollingerValue;
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<BollingerValue> {
+        self.update(candle.close)
+    }
+
+    fn get_value(&self) -> Option<BollingerValue> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        *self = Bollinger::new(self.period, self.multiplier);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::assert_close;
+
+    #[test]
+    fn bands_are_two_deviations_from_the_mean() {
+        let mut bollinger = Bollinger::new(8, 2.0);
+        let closes = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0];
+        for close in closes {
+            assert_eq!(bollinger.update(close), None);
+        }
+
+        // Mean 5 and population standard deviation 2.
+        let value = bollinger.update(9.0).unwrap();
+        assert_close(value.middle, 5.0);
+        assert_close(value.upper, 9.0);
+        assert_close(value.lower, 1.0);
+        assert_close(value.percent_b, 1.0);
+        assert_close(value.bandwidth, 1.6);
+
+        // The 2 drops out of the window, sum 43 and sum of squares 253.
+        let value = bollinger.update(5.0).unwrap();
+        let std_dev = (253.0_f64 / 8.0 - 5.375 * 5.375).sqrt();
+        assert_close(value.middle, 5.375);
+        assert_close(value.upper, 5.375 + 2.0 * std_dev);
+        assert_close(value.percent_b, (5.0 - value.lower) / (4.0 * std_dev));
+    }
+
+    #[test]
+    fn flat_series_sits_in_the_middle() {
+        let mut bollinger = Bollinger::new(3, 2.0);
+        let value = [10.0; 3].map(|close| bollinger.update(close))[2].unwrap();
+
+        assert_close(value.upper, 10.0);
+        assert_close(value.lower, 10.0);
+        assert_close(value.percent_b, 0.5);
+        assert_close(value.bandwidth, 0.0);
+    }
+}
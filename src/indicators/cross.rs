@@ -0,0 +1,144 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CrossEvent {
+    CrossUp,
+    CrossDown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cross {
+    pub event: CrossEvent,
+    // 0 when the cross happened on the latest update.
+    pub bars_ago: usize,
+}
+
+// Tracks two series, e.g. macd and signal line, and records when the first
+// crosses above or below the second. Touching the second and turning back
+// isn't a cross, only a move to the other side of it is.
+#[derive(Debug, Default)]
+pub struct Crossover {
+    // Last difference that wasn't zero, so a touch keeps the side it came from.
+    prev_diff: Option<f64>,
+    last_event: Option<CrossEvent>,
+    bars_since: usize,
+}
+
+impl Crossover {
+    pub fn new() -> Self {
+        Crossover::default()
+    }
+
+    pub fn update(&mut self, fast: f64, slow: f64) -> Option<CrossEvent> {
+        let diff = fast - slow;
+
+        let event = match self.prev_diff {
+            Some(prev) if prev < 0.0 && diff > 0.0 => Some(CrossEvent::CrossUp),
+            Some(prev) if prev > 0.0 && diff < 0.0 => Some(CrossEvent::CrossDown),
+            _ => None,
+        };
+
+        if diff != 0.0 {
+            self.prev_diff = Some(diff);
+        }
+
+        if event.is_some() {
+            self.last_event = event;
+            self.bars_since = 0;
+        } else {
+            self.bars_since += 1;
+        }
+
+        event
+    }
+
+    pub fn reset(&mut self) {
+        *self = Crossover::default();
+    }
+
+    pub fn get_last_cross(&self) -> Option<Cross> {
+        self.last_event.map(|event| Cross {
+            event,
+            bars_ago: self.bars_since,
+        })
+    }
+
+    // The most recent cross if it happened within the last `bars` updates.
+    pub fn crossed_within(&self, bars: usize) -> Option<Cross> {
+        self.get_last_cross().filter(|cross| cross.bars_ago < bars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Feeds each value against a flat series at 0.
+    fn feed(crossover: &mut Crossover, values: &[f64]) -> Vec<Option<CrossEvent>> {
+        values
+            .iter()
+            .map(|value| crossover.update(*value, 0.0))
+            .collect()
+    }
+
+    #[test]
+    fn crosses() {
+        let mut crossover = Crossover::new();
+
+        assert_eq!(
+            feed(&mut crossover, &[-1.0, 1.0, 2.0, -1.0]),
+            [
+                None,
+                Some(CrossEvent::CrossUp),
+                None,
+                Some(CrossEvent::CrossDown)
+            ]
+        );
+        assert_eq!(
+            crossover.get_last_cross(),
+            Some(Cross {
+                event: CrossEvent::CrossDown,
+                bars_ago: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn cross_through_a_touch() {
+        let mut crossover = Crossover::new();
+
+        assert_eq!(
+            feed(&mut crossover, &[-1.0, 0.0, 0.0, 1.0]),
+            [None, None, None, Some(CrossEvent::CrossUp)]
+        );
+        assert_eq!(
+            feed(&mut crossover, &[0.0, -1.0]),
+            [None, Some(CrossEvent::CrossDown)]
+        );
+    }
+
+    #[test]
+    fn touch_and_bounce_is_not_a_cross() {
+        let mut crossover = Crossover::new();
+
+        assert_eq!(feed(&mut crossover, &[1.0, 0.0, 1.0]), [None; 3]);
+        assert_eq!(
+            feed(&mut crossover, &[-1.0, 0.0, -1.0]),
+            [Some(CrossEvent::CrossDown), None, None]
+        );
+        assert_eq!(crossover.get_last_cross().unwrap().bars_ago, 2);
+    }
+
+    #[test]
+    fn crossed_within() {
+        let mut crossover = Crossover::new();
+        feed(&mut crossover, &[-1.0, 1.0, 2.0, 3.0]);
+
+        assert_eq!(crossover.crossed_within(2), None);
+        assert_eq!(
+            crossover.crossed_within(3).map(|cross| cross.event),
+            Some(CrossEvent::CrossUp)
+        );
+
+        crossover.reset();
+        assert_eq!(crossover.get_last_cross(), None);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::model::event::Candlestick;
+
+use super::{ema::Ema, Indicator};
+
+// Double ema, 2 * ema - ema(ema).
+#[derive(Debug)]
+pub struct Dema {
+    ema: Ema,
+    ema_of_ema: Ema,
+    current: Option<f64>,
+}
+
+impl Dema {
+    pub fn new(period: usize) -> Self {
+        Dema {
+            ema: Ema::new(period),
+            ema_of_ema: Ema::new(period),
+            current: None,
+        }
+    }
+
+    pub fn update(&mut self, price: f64) -> Option<f64> {
+        if let Some(ema) = self.ema.update(price) {
+            if let Some(ema_of_ema) = self.ema_of_ema.update(ema) {
+                self.current = Some(2.0 * ema - ema_of_ema);
+            }
+        }
+
+        self.current
+    }
+}
+
+impl Indicator for Dema {
+    type Output = f64;
+
+    fn warmup_period(&self) -> usize {
+        self.ema.warmup_period() * 2 - 1
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<f64> {
+        self.update(candle.close)
+    }
+
+    fn get_value(&self) -> Option<f64> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        self.ema.reset();
+        self.ema_of_ema.reset();
+        self.current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::assert_close;
+
+    #[test]
+    fn doubles_the_ema_and_subtracts_its_smoothing() {
+        let mut dema = Dema::new(2);
+        assert_eq!(dema.warmup_period(), 3);
+
+        // The ema is seeded with 2, stays at 2 and then moves to 14/3. The
+        // ema of that is seeded with 2 and then moves to 34/9.
+        assert_eq!(dema.update(1.0), None);
+        assert_eq!(dema.update(3.0), None);
+        assert_close(dema.update(2.0).unwrap(), 2.0);
+        assert_close(dema.update(6.0).unwrap(), 2.0 * 14.0 / 3.0 - 34.0 / 9.0);
+    }
+
+    #[test]
+    fn is_built_from_nested_emas() {
+        let mut dema = Dema::new(5);
+        let mut ema = Ema::new(5);
+        let mut ema_of_ema = Ema::new(5);
+
+        for i in 0..40 {
+            let price = 100.0 + ((i * 7) % 13) as f64;
+            let value = dema.update(price);
+
+            match ema
+                .update(price)
+                .and_then(|e| Some((e, ema_of_ema.update(e)?)))
+            {
+                Some((e, ee)) => assert_close(value.unwrap(), 2.0 * e - ee),
+                None => assert_eq!(value, None),
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::model::event::Candlestick;
+
+use super::{rolling::RollingExtreme, Indicator};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DonchianValue {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+// Highest high and lowest low of the last `period` candles.
+#[derive(Debug)]
+pub struct Donchian {
+    period: usize,
+    highest: RollingExtreme,
+    lowest: RollingExtreme,
+    current: Option<DonchianValue>,
+    previous: Option<DonchianValue>,
+}
+
+impl Donchian {
+    pub fn new(period: usize) -> Self {
+        Donchian {
+            period,
+            highest: RollingExtreme::max(period),
+            lowest: RollingExtreme::min(period),
+            current: None,
+            previous: None,
+        }
+    }
+
+    // Channel before the latest candle, breakouts are measured against this.
+    #[inline]
+    pub fn get_previous(&self) -> Option<DonchianValue> {
+        self.previous
+    }
+}
+
+impl Indicator for Donchian {
+    type Output = DonchianValue;
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<DonchianValue> {
+        self.previous = self.current;
+
+        if let (Some(upper), Some(lower)) = (
+            self.highest.update(candle.high),
+            self.lowest.update(candle.low),
+        ) {
+            self.current = Some(DonchianValue {
+                upper,
+                middle: (upper + lower) / 2.0,
+                lower,
+            });
+        }
+
+        self.current
+    }
+
+    fn get_value(&self) -> Option<DonchianValue> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        self.highest.reset();
+        self.lowest.reset();
+        self.current = None;
+        self.previous = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::ohlcv;
+
+    fn channel(upper: f64, lower: f64) -> Option<DonchianValue> {
+        Some(DonchianValue {
+            upper,
+            middle: (upper + lower) / 2.0,
+            lower,
+        })
+    }
+
+    #[test]
+    fn tracks_the_window_extremes() {
+        let mut donchian = Donchian::new(3);
+        let bars = [
+            (10.0, 8.0),
+            (12.0, 9.0),
+            (11.0, 7.0),
+            (9.0, 8.0),
+            (10.0, 9.0),
+        ];
+        let values: Vec<Option<DonchianValue>> = bars
+            .iter()
+            .map(|&(high, low)| donchian.on_candle(&ohlcv(low, high, low, low, 0.0)))
+            .collect();
+
+        assert_eq!(
+            values,
+            [
+                None,
+                None,
+                channel(12.0, 7.0),
+                channel(12.0, 7.0),
+                channel(11.0, 7.0)
+            ]
+        );
+        assert_eq!(donchian.get_previous(), channel(12.0, 7.0));
+    }
+
+    #[test]
+    fn reset_clears_the_previous_channel() {
+        let mut donchian = Donchian::new(1);
+        donchian.on_candle(&ohlcv(1.0, 2.0, 1.0, 1.0, 0.0));
+        donchian.on_candle(&ohlcv(1.0, 3.0, 1.0, 1.0, 0.0));
+        assert_eq!(donchian.get_previous(), channel(2.0, 1.0));
+
+        donchian.reset();
+        assert_eq!(donchian.get_value(), None);
+        assert_eq!(donchian.get_previous(), None);
+    }
+}
@@ -1,5 +1,9 @@
 use std::fmt;
 
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
 #[derive(Debug)]
 pub struct Ema {
     period: usize,
@@ -37,14 +41,30 @@ impl Ema {
 
         self.current
     }
+}
+
+impl Indicator for Ema {
+    type Output = f64;
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<f64> {
+        self.update(candle.close)
+    }
+
+    fn get_value(&self) -> Option<f64> {
+        self.current
+    }
 
-    pub fn get_ema(&self) -> f64 {
-        self.current.unwrap_or(0.0)
+    fn reset(&mut self) {
+        *self = Ema::new(self.period);
     }
 }
 
 impl fmt::Display for Ema {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Ema: {}", self.current.unwrap_or(0.0))
+        write!(f, "Ema: {:?}", self.current)
     }
 }
@@ -0,0 +1,85 @@
+use crate::model::event::Candlestick;
+
+use super::{wma::Wma, Indicator};
+
+// Hull moving average, wma(2 * wma(n / 2) - wma(n), sqrt(n)).
+#[derive(Debug)]
+pub struct Hma {
+    half: Wma,
+    full: Wma,
+    smooth: Wma,
+}
+
+impl Hma {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(2);
+
+        Hma {
+            half: Wma::new(period / 2),
+            full: Wma::new(period),
+            smooth: Wma::new((period as f64).sqrt().round() as usize),
+        }
+    }
+
+    pub fn update(&mut self, price: f64) -> Option<f64> {
+        match (self.half.update(price), self.full.update(price)) {
+            (Some(half), Some(full)) => self.smooth.update(2.0 * half - full),
+            _ => None,
+        }
+    }
+}
+
+impl Indicator for Hma {
+    type Output = f64;
+
+    fn warmup_period(&self) -> usize {
+        self.full.warmup_period() + self.smooth.warmup_period() - 1
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<f64> {
+        self.update(candle.close)
+    }
+
+    fn get_value(&self) -> Option<f64> {
+        self.smooth.get_value()
+    }
+
+    fn reset(&mut self) {
+        self.half.reset();
+        self.full.reset();
+        self.smooth.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::assert_close;
+
+    #[test]
+    fn follows_a_straight_line_without_lag() {
+        // Period 4 is wma(2 * wma(2) - wma(4), 2). On a line wma(2) lags by
+        // 1/3 and wma(4) by 1, so the raw series leads by 1/3 and the final
+        // wma(2) pulls it back onto the price.
+        let mut hma = Hma::new(4);
+        assert_eq!(hma.warmup_period(), 5);
+
+        for price in 1..=4 {
+            assert_eq!(hma.update(price as f64), None);
+        }
+        assert_close(hma.update(5.0).unwrap(), 5.0);
+        assert_close(hma.update(6.0).unwrap(), 6.0);
+    }
+
+    #[test]
+    fn reacts_to_a_jump() {
+        let mut hma = Hma::new(4);
+        for price in 1..=5 {
+            hma.update(price as f64);
+        }
+
+        // wma(2) = (5 + 2 * 11) / 3 = 9, wma(4) = (3 + 8 + 15 + 44) / 10 = 7,
+        // so the raw value is 11 and the previous one was 5 + 1/3.
+        assert_close(hma.update(11.0).unwrap(), (16.0 / 3.0 + 2.0 * 11.0) / 3.0);
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::VecDeque;
+
+use crate::model::event::Candlestick;
+
+use super::{rolling::RollingExtreme, Indicator};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IchimokuValue {
+    pub tenkan: f64,
+    pub kijun: f64,
+    // Cloud under the current candle, calculated `displacement` candles ago.
+    pub senkou_a: f64,
+    pub senkou_b: f64,
+    // Cloud calculated from the current candle, drawn `displacement` candles ahead.
+    pub leading_a: f64,
+    pub leading_b: f64,
+    // The chikou span is the current close drawn `displacement` candles back,
+    // so it is compared against the close from that candle.
+    pub chikou: f64,
+    pub chikou_reference: f64,
+}
+
+impl IchimokuValue {
+    pub fn cloud_top(&self) -> f64 {
+        self.senkou_a.max(self.senkou_b)
+    }
+
+    pub fn cloud_bottom(&self) -> f64 {
+        self.senkou_a.min(self.senkou_b)
+    }
+}
+
+// Middle of the highest high and lowest low over a period.
+#[derive(Debug)]
+struct Midpoint {
+    highest: RollingExtreme,
+    lowest: RollingExtreme,
+}
+
+impl Midpoint {
+    fn new(period: usize) -> Self {
+        Midpoint {
+            highest: RollingExtreme::max(period),
+            lowest: RollingExtreme::min(period),
+        }
+    }
+
+    fn update(&mut self, high: f64, low: f64) -> Option<f64> {
+        match (self.highest.update(high), self.lowest.update(low)) {
+            (Some(high), Some(low)) => Some((high + low) / 2.0),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Ichimoku {
+    senkou_b_period: usize,
+    displacement: usize,
+    tenkan: Midpoint,
+    kijun: Midpoint,
+    senkou_b: Midpoint,
+    // Leading spans waiting to be displaced under a future candle.
+    cloud: VecDeque<(f64, f64)>,
+    closes: VecDeque<f64>,
+    current: Option<IchimokuValue>,
+}
+
+impl Ichimoku {
+    pub fn new(
+        tenkan_period: usize,
+        kijun_period: usize,
+        senkou_b_period: usize,
+        displacement: usize,
+    ) -> Self {
+        Ichimoku {
+            senkou_b_period,
+            displacement,
+            tenkan: Midpoint::new(tenkan_period),
+            kijun: Midpoint::new(kijun_period),
+            senkou_b: Midpoint::new(senkou_b_period),
+            cloud: VecDeque::with_capacity(displacement + 1),
+            closes: VecDeque::with_capacity(displacement + 1),
+            current: None,
+        }
+    }
+}
+
+impl Indicator for Ichimoku {
+    type Output = IchimokuValue;
+
+    // The slowest span has to be calculated and then displaced.
+    fn warmup_period(&self) -> usize {
+        self.senkou_b_period + self.displacement
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<IchimokuValue> {
+        self.closes.push_back(candle.close);
+        if self.closes.len() > self.displacement + 1 {
+            self.closes.pop_front();
+        }
+
+        let tenkan = self.tenkan.update(candle.high, candle.low);
+        let kijun = self.kijun.update(candle.high, candle.low);
+        let senkou_b = self.senkou_b.update(candle.high, candle.low);
+
+        let (tenkan, kijun, leading_b) = match (tenkan, kijun, senkou_b) {
+            (Some(tenkan), Some(kijun), Some(senkou_b)) => (tenkan, kijun, senkou_b),
+            _ => return None,
+        };
+        let leading_a = (tenkan + kijun) / 2.0;
+
+        self.cloud.push_back((leading_a, leading_b));
+        if self.cloud.len() <= self.displacement {
+            return None;
+        }
+
+        let (senkou_a, senkou_b) = self.cloud.pop_front()?;
+
+        self.current = Some(IchimokuValue {
+            tenkan,
+            kijun,
+            senkou_a,
+            senkou_b,
+            leading_a,
+            leading_b,
+            chikou: candle.close,
+            chikou_reference: self.closes.front().copied().unwrap_or(candle.close),
+        });
+        self.current
+    }
+
+    fn get_value(&self) -> Option<IchimokuValue> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        for midpoint in [&mut self.tenkan, &mut self.kijun, &mut self.senkou_b] {
+            midpoint.highest.reset();
+            midpoint.lowest.reset();
+        }
+        self.cloud.clear();
+        self.closes.clear();
+        self.current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::{assert_resets, ohlcv};
+
+    // Candles one either side of the close.
+    fn next(ichimoku: &mut Ichimoku, close: f64) -> Option<IchimokuValue> {
+        ichimoku.on_candle(&ohlcv(close, close + 1.0, close - 1.0, close, 0.0))
+    }
+
+    #[test]
+    fn displaces_the_cloud() {
+        let mut ichimoku = Ichimoku::new(1, 2, 3, 1);
+        for close in [10.0, 12.0, 14.0] {
+            assert_eq!(next(&mut ichimoku, close), None);
+        }
+
+        // The cloud from the 14 candle, tenkan 14 and kijun 13 with senkou b
+        // 12, is now under the 16 candle.
+        assert_eq!(
+            next(&mut ichimoku, 16.0),
+            Some(IchimokuValue {
+                tenkan: 16.0,
+                kijun: 15.0,
+                senkou_a: 13.5,
+                senkou_b: 12.0,
+                leading_a: 15.5,
+                leading_b: 14.0,
+                chikou: 16.0,
+                chikou_reference: 14.0,
+            })
+        );
+
+        let value = next(&mut ichimoku, 18.0).unwrap();
+        assert_eq!((value.tenkan, value.kijun), (18.0, 17.0));
+        assert_eq!((value.leading_a, value.leading_b), (17.5, 16.0));
+        assert_eq!((value.cloud_top(), value.cloud_bottom()), (15.5, 14.0));
+        assert_eq!(value.chikou_reference, 16.0);
+    }
+
+    #[test]
+    fn warms_up_over_senkou_b_and_the_displacement() {
+        let mut ichimoku = Ichimoku::new(2, 3, 5, 3);
+        let readings = (0..ichimoku.warmup_period())
+            .filter_map(|i| next(&mut ichimoku, 10.0 + i as f64))
+            .count();
+        assert_eq!(readings, 1);
+    }
+
+    #[test]
+    fn reset_starts_over() {
+        let candles: Vec<_> = [10.0, 12.0, 11.0, 14.0, 13.0, 15.0, 12.0, 16.0]
+            .iter()
+            .map(|&close| ohlcv(close, close + 1.0, close - 1.0, close, 0.0))
+            .collect();
+        assert_resets(&mut Ichimoku::new(2, 3, 5, 2), &candles);
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
+const FAST_PERIOD: f64 = 2.0;
+const SLOW_PERIOD: f64 = 30.0;
+
+// Kaufman adaptive moving average, follows price closely while it trends
+// and flattens out while it chops around.
+#[derive(Debug)]
+pub struct Kama {
+    period: usize,
+    prices: VecDeque<f64>,
+    // Sum of the absolute price changes across the window.
+    volatility: f64,
+    current: Option<f64>,
+}
+
+impl Kama {
+    pub fn new(period: usize) -> Self {
+        Kama {
+            period,
+            prices: VecDeque::with_capacity(period + 1),
+            volatility: 0.0,
+            current: None,
+        }
+    }
+
+    pub fn update(&mut self, price: f64) -> Option<f64> {
+        if let Some(last) = self.prices.back() {
+            self.volatility += (price - last).abs();
+        }
+        self.prices.push_back(price);
+
+        if self.prices.len() > self.period + 1 {
+            if let (Some(oldest), Some(next)) = (self.prices.pop_front(), self.prices.front()) {
+                self.volatility -= (next - oldest).abs();
+            }
+        }
+
+        if self.prices.len() <= self.period {
+            return None;
+        }
+
+        let change = (price - self.prices.front().unwrap_or(&price)).abs();
+        let efficiency = if self.volatility > 0.0 {
+            change / self.volatility
+        } else {
+            0.0
+        };
+
+        let fast = 2.0 / (FAST_PERIOD + 1.0);
+        let slow = 2.0 / (SLOW_PERIOD + 1.0);
+        let smoothing = (efficiency * (fast - slow) + slow).powi(2);
+
+        // Start from the previous price the first time round.
+        let prev = self.current.unwrap_or(self.prices[self.prices.len() - 2]);
+        self.current = Some(prev + smoothing * (price - prev));
+        self.current
+    }
+}
+
+impl Indicator for Kama {
+    type Output = f64;
+
+    fn warmup_period(&self) -> usize {
+        self.period + 1
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<f64> {
+        self.update(candle.close)
+    }
+
+    fn get_value(&self) -> Option<f64> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        *self = Kama::new(self.period);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::assert_close;
+
+    const FAST: f64 = 2.0 / 3.0;
+    const SLOW: f64 = 2.0 / 31.0;
+
+    #[test]
+    fn trend_uses_the_fast_constant() {
+        // A straight line has an efficiency ratio of 1.
+        let mut kama = Kama::new(3);
+
+        assert_eq!(kama.update(1.0), None);
+        assert_eq!(kama.update(2.0), None);
+        assert_eq!(kama.update(3.0), None);
+
+        // Starts from the previous price, 3 + (2/3)^2 * (4 - 3).
+        let first = 3.0 + FAST * FAST;
+        assert_close(kama.update(4.0).unwrap(), first);
+        assert_close(
+            kama.update(5.0).unwrap(),
+            first + FAST * FAST * (5.0 - first),
+        );
+    }
+
+    #[test]
+    fn chop_uses_the_slow_constant() {
+        // Back where it started, so the efficiency ratio is 0.
+        let mut kama = Kama::new(2);
+
+        kama.update(1.0);
+        kama.update(2.0);
+        assert_close(kama.update(1.0).unwrap(), 2.0 - SLOW * SLOW);
+    }
+
+    #[test]
+    fn efficiency_ratio_is_net_change_over_path_length() {
+        let mut kama = Kama::new(3);
+        kama.update(10.0);
+        kama.update(11.0);
+        kama.update(10.0);
+
+        // Net change 2 over a path of 1 + 1 + 2 = 4.
+        let smoothing = (0.5 * (FAST - SLOW) + SLOW).powi(2);
+        let first = 10.0 + smoothing * 2.0;
+        assert_close(kama.update(12.0).unwrap(), first);
+
+        // The window is now 11, 10, 12, 11, with no net change.
+        let second = first + SLOW * SLOW * (11.0 - first);
+        assert_close(kama.update(11.0).unwrap(), second);
+    }
+}
@@ -0,0 +1,99 @@
+use crate::model::event::Candlestick;
+
+use super::{atr::Atr, ema::Ema, Indicator};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeltnerValue {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+// Ema of the close plus and minus `multiplier` times the atr.
+#[derive(Debug)]
+pub struct Keltner {
+    ema: Ema,
+    atr: Atr,
+    multiplier: f64,
+    current: Option<KeltnerValue>,
+}
+
+impl Keltner {
+    pub fn new(ema_period: usize, atr_period: usize, multiplier: f64) -> Self {
+        Keltner {
+            ema: Ema::new(ema_period),
+            atr: Atr::new(atr_period),
+            multiplier,
+            current: None,
+        }
+    }
+}
+
+impl Indicator for Keltner {
+    type Output = KeltnerValue;
+
+    fn warmup_period(&self) -> usize {
+        self.ema.warmup_period().max(self.atr.warmup_period())
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<KeltnerValue> {
+        if let (Some(middle), Some(atr)) = (self.ema.on_candle(candle), self.atr.on_candle(candle))
+        {
+            self.current = Some(KeltnerValue {
+                upper: middle + self.multiplier * atr,
+                middle,
+                lower: middle - self.multiplier * atr,
+            });
+        }
+
+        self.current
+    }
+
+    fn get_value(&self) -> Option<KeltnerValue> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        self.ema.reset();
+        self.atr.reset();
+        self.current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::{assert_close, assert_resets, ohlcv};
+
+    #[test]
+    fn bands_are_atr_multiples_around_the_ema() {
+        let mut keltner = Keltner::new(2, 2, 2.0);
+        assert_eq!(keltner.on_candle(&ohlcv(10.0, 11.0, 9.0, 10.0, 0.0)), None);
+
+        // Ema 11 from the first two closes, atr of true ranges 2 and 3.
+        let value = keltner
+            .on_candle(&ohlcv(10.0, 13.0, 11.0, 12.0, 0.0))
+            .unwrap();
+        assert_close(value.middle, 11.0);
+        assert_close(value.upper, 16.0);
+        assert_close(value.lower, 6.0);
+
+        // A close on the ema keeps it at 11, the atr smooths to 2.25.
+        let value = keltner
+            .on_candle(&ohlcv(12.0, 12.0, 10.0, 11.0, 0.0))
+            .unwrap();
+        assert_close(value.middle, 11.0);
+        assert_close(value.upper, 15.5);
+        assert_close(value.lower, 6.5);
+    }
+
+    #[test]
+    fn reset_starts_over() {
+        let candles = [
+            ohlcv(10.0, 11.0, 9.0, 10.0, 0.0),
+            ohlcv(10.0, 13.0, 11.0, 12.0, 0.0),
+            ohlcv(12.0, 12.0, 10.0, 11.0, 0.0),
+        ];
+        assert_resets(&mut Keltner::new(2, 2, 2.0), &candles);
+    }
+}
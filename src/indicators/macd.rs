@@ -1,49 +1,97 @@
 use std::fmt;
 
-use super::ema::Ema;
+use crate::model::event::Candlestick;
 
+use super::{
+    moving_average::{MaType, MovingAverage},
+    Indicator,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdValue {
+    pub macd: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+// Difference between a fast and slow average of price, with a signal line
+// averaging that difference. Classic macd uses emas for all three.
 #[derive(Debug)]
 pub struct Macd {
-    fast_ema: Ema,
-    slow_ema: Ema,
-    signal_ema: Ema,
-    macd_value: f64,
-    signal: f64,
+    fast_average: Box<dyn MovingAverage>,
+    slow_average: Box<dyn MovingAverage>,
+    signal_average: Box<dyn MovingAverage>,
+    macd_value: Option<f64>,
+    signal: Option<f64>,
 }
 
 impl Macd {
-    pub fn new(fast_length: usize, slow_length: usize, signal_length: usize) -> Self {
+    pub fn new(
+        ma_type: MaType,
+        fast_length: usize,
+        slow_length: usize,
+        signal_length: usize,
+    ) -> Self {
         Macd {
-            fast_ema: Ema::new(fast_length),
-            slow_ema: Ema::new(slow_length),
-            signal_ema: Ema::new(signal_length),
-            macd_value: 0.0,
-            signal: 0.0,
+            fast_average: ma_type.build(fast_length),
+            slow_average: ma_type.build(slow_length),
+            signal_average: ma_type.build(signal_length),
+            macd_value: None,
+            signal: None,
         }
     }
 
-    pub fn update(&mut self, price: f64) {
-        if let (Some(fast_ema), Some(slow_ema)) =
-            (self.fast_ema.update(price), self.slow_ema.update(price))
-        {
-            self.macd_value = fast_ema - slow_ema;
-            self.signal = self.signal_ema.update(self.macd_value).unwrap_or(0.0);
+    pub fn update(&mut self, price: f64) -> Option<MacdValue> {
+        if let (Some(fast_average), Some(slow_average)) = (
+            self.fast_average.update(price),
+            self.slow_average.update(price),
+        ) {
+            let macd_value = fast_average - slow_average;
+            self.macd_value = Some(macd_value);
+            self.signal = self.signal_average.update(macd_value);
         }
+
+        self.get_value()
     }
+}
+
+impl Indicator for Macd {
+    type Output = MacdValue;
 
-    #[inline]
-    pub fn get_macd_line(&self) -> f64 {
-        self.macd_value
+    fn warmup_period(&self) -> usize {
+        self.fast_average
+            .warmup_period()
+            .max(self.slow_average.warmup_period())
+            + self.signal_average.warmup_period()
+            - 1
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<MacdValue> {
+        self.update(candle.close)
+    }
+
+    fn get_value(&self) -> Option<MacdValue> {
+        match (self.macd_value, self.signal) {
+            (Some(macd), Some(signal)) => Some(MacdValue {
+                macd,
+                signal,
+                histogram: macd - signal,
+            }),
+            _ => None,
+        }
     }
 
-    #[inline]
-    pub fn get_signal_line(&self) -> f64 {
-        self.signal
+    fn reset(&mut self) {
+        self.fast_average.reset();
+        self.slow_average.reset();
+        self.signal_average.reset();
+        self.macd_value = None;
+        self.signal = None;
     }
 }
 
 impl fmt::Display for Macd {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Value: {}, Signal: {}", self.macd_value, self.signal)
+        write!(f, "Value: {:?}, Signal: {:?}", self.macd_value, self.signal)
     }
 }
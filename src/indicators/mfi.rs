@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
+// Money flow index, a volume weighted rsi of the typical price.
+#[derive(Debug)]
+pub struct Mfi {
+    period: usize,
+    prev_typical_price: Option<f64>,
+    // (positive flow, negative flow) per candle.
+    flows: VecDeque<(f64, f64)>,
+    positive_sum: f64,
+    negative_sum: f64,
+    current: Option<f64>,
+}
+
+impl Mfi {
+    pub fn new(period: usize) -> Self {
+        Mfi {
+            period,
+            prev_typical_price: None,
+            flows: VecDeque::with_capacity(period),
+            positive_sum: 0.0,
+            negative_sum: 0.0,
+            current: None,
+        }
+    }
+}
+
+impl Indicator for Mfi {
+    type Output = f64;
+
+    fn warmup_period(&self) -> usize {
+        self.period + 1
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<f64> {
+        let typical_price = (candle.high + candle.low + candle.close) / 3.0;
+        let money_flow = typical_price * candle.volume;
+
+        if let Some(prev) = self.prev_typical_price {
+            let flow = if typical_price > prev {
+                (money_flow, 0.0)
+            } else if typical_price < prev {
+                (0.0, money_flow)
+            } else {
+                (0.0, 0.0)
+            };
+
+            self.flows.push_back(flow);
+            self.positive_sum += flow.0;
+            self.negative_sum += flow.1;
+
+            if self.flows.len() > self.period {
+                let (positive, negative) = self.flows.pop_front().unwrap_or_default();
+                self.positive_sum -= positive;
+                self.negative_sum -= negative;
+            }
+
+            if self.flows.len() == self.period {
+                let total = self.positive_sum + self.negative_sum;
+                self.current = Some(if total > 0.0 {
+                    100.0 * self.positive_sum / total
+                } else {
+                    50.0
+                });
+            }
+        }
+
+        self.prev_typical_price = Some(typical_price);
+        self.current
+    }
+
+    fn get_value(&self) -> Option<f64> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        *self = Mfi::new(self.period);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::{assert_close, ohlcv};
+
+    fn next(mfi: &mut Mfi, price: f64, volume: f64) -> Option<f64> {
+        mfi.on_candle(&ohlcv(price, price, price, price, volume))
+    }
+
+    #[test]
+    fn ratio_of_positive_money_flow() {
+        let mut mfi = Mfi::new(2);
+        assert_eq!(next(&mut mfi, 10.0, 1.0), None);
+        assert_eq!(next(&mut mfi, 11.0, 2.0), None);
+
+        // 22 of positive flow against 10 of negative.
+        assert_close(next(&mut mfi, 10.0, 1.0).unwrap(), 68.75);
+        // The 22 drops out, 12 against 10.
+        assert_close(next(&mut mfi, 12.0, 1.0).unwrap(), 1200.0 / 22.0);
+        // An unchanged price adds no flow either way.
+        assert_close(next(&mut mfi, 12.0, 5.0).unwrap(), 100.0);
+        assert_close(next(&mut mfi, 12.0, 5.0).unwrap(), 50.0);
+    }
+}
@@ -1,6 +1,54 @@
+use std::fmt;
+
+use crate::model::event::Candlestick;
+
+pub mod adx;
 pub mod atr;
+pub mod bollinger;
+pub mod cross;
+pub mod dema;
+pub mod donchian;
 pub mod ema;
+pub mod hma;
+pub mod ichimoku;
+pub mod kama;
+pub mod keltner;
 pub mod macd;
+pub mod mfi;
+pub mod moving_average;
+pub mod obv;
+pub mod parabolic_sar;
+pub mod pattern;
+pub mod pivot;
+pub mod rolling;
 pub mod rsi;
+pub mod sma;
 pub mod stoch;
 pub mod stoch_rsi;
+pub mod supertrend;
+pub mod swing;
+pub mod tema;
+#[cfg(test)]
+pub mod testing;
+pub mod volume_profile;
+pub mod vwap;
+pub mod wma;
+
+// Shared interface so strategies can feed every indicator the same closed candles.
+pub trait Indicator: fmt::Debug + Send {
+    type Output: Copy + fmt::Debug;
+
+    // Number of candles needed before a value is produced.
+    fn warmup_period(&self) -> usize;
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<Self::Output>;
+
+    // None until the indicator has warmed up.
+    fn get_value(&self) -> Option<Self::Output>;
+
+    fn is_ready(&self) -> bool {
+        self.get_value().is_some()
+    }
+
+    fn reset(&mut self);
+}
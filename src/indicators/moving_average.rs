@@ -0,0 +1,128 @@
+use serde::Deserialize;
+
+use super::{
+    dema::Dema, ema::Ema, hma::Hma, kama::Kama, sma::Sma, tema::Tema, wma::Wma, Indicator,
+};
+
+// Shorter periods don't average anything, and kama starts from the price
+// before the latest one.
+const MIN_PERIOD: usize = 2;
+
+// Averages that can also be fed values that aren't candle closes, such as
+// the macd line.
+pub trait MovingAverage: Indicator<Output = f64> {
+    fn update(&mut self, value: f64) -> Option<f64>;
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaType {
+    Sma,
+    #[default]
+    Ema,
+    Wma,
+    Hma,
+    Dema,
+    Tema,
+    Kama,
+}
+
+impl MaType {
+    pub fn validate_period(self, period: usize) -> Result<(), String> {
+        if period < MIN_PERIOD {
+            return Err(format!(
+                "{:?} period must be at least {}, got {}",
+                self, MIN_PERIOD, period
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn build(self, period: usize) -> Box<dyn MovingAverage> {
+        match self {
+            MaType::Sma => Box::new(Sma::new(period)),
+            MaType::Ema => Box::new(Ema::new(period)),
+            MaType::Wma => Box::new(Wma::new(period)),
+            MaType::Hma => Box::new(Hma::new(period)),
+            MaType::Dema => Box::new(Dema::new(period)),
+            MaType::Tema => Box::new(Tema::new(period)),
+            MaType::Kama => Box::new(Kama::new(period)),
+        }
+    }
+}
+
+impl MovingAverage for Sma {
+    fn update(&mut self, value: f64) -> Option<f64> {
+        Sma::update(self, value)
+    }
+}
+
+impl MovingAverage for Ema {
+    fn update(&mut self, value: f64) -> Option<f64> {
+        Ema::update(self, value)
+    }
+}
+
+impl MovingAverage for Wma {
+    fn update(&mut self, value: f64) -> Option<f64> {
+        Wma::update(self, value)
+    }
+}
+
+impl MovingAverage for Hma {
+    fn update(&mut self, value: f64) -> Option<f64> {
+        Hma::update(self, value)
+    }
+}
+
+impl MovingAverage for Dema {
+    fn update(&mut self, value: f64) -> Option<f64> {
+        Dema::update(self, value)
+    }
+}
+
+impl MovingAverage for Tema {
+    fn update(&mut self, value: f64) -> Option<f64> {
+        Tema::update(self, value)
+    }
+}
+
+impl MovingAverage for Kama {
+    fn update(&mut self, value: f64) -> Option<f64> {
+        Kama::update(self, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [MaType; 7] = [
+        MaType::Sma,
+        MaType::Ema,
+        MaType::Wma,
+        MaType::Hma,
+        MaType::Dema,
+        MaType::Tema,
+        MaType::Kama,
+    ];
+
+    #[test]
+    fn rejects_short_periods() {
+        for ma_type in ALL {
+            assert!(ma_type.validate_period(0).is_err());
+            assert!(ma_type.validate_period(1).is_err());
+            assert!(ma_type.validate_period(MIN_PERIOD).is_ok());
+        }
+    }
+
+    #[test]
+    fn shortest_period_warms_up() {
+        for ma_type in ALL {
+            let mut average = ma_type.build(MIN_PERIOD);
+            let values: Vec<Option<f64>> = (1..=20).map(|i| average.update(i as f64)).collect();
+
+            assert!(values.last().unwrap().is_some(), "{:?}", ma_type);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
+// On balance volume, adds the volume on up closes and subtracts it on down closes.
+#[derive(Debug, Default)]
+pub struct Obv {
+    prev_close: Option<f64>,
+    current: Option<f64>,
+}
+
+impl Obv {
+    pub fn new() -> Self {
+        Obv::default()
+    }
+}
+
+impl Indicator for Obv {
+    type Output = f64;
+
+    fn warmup_period(&self) -> usize {
+        2
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<f64> {
+        if let Some(prev_close) = self.prev_close {
+            let obv = self.current.unwrap_or(0.0);
+
+            self.current = Some(if candle.close > prev_close {
+                obv + candle.volume
+            } else if candle.close < prev_close {
+                obv - candle.volume
+            } else {
+                obv
+            });
+        }
+
+        self.prev_close = Some(candle.close);
+        self.current
+    }
+
+    fn get_value(&self) -> Option<f64> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        *self = Obv::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::{assert_resets, ohlcv};
+
+    #[test]
+    fn adds_up_and_subtracts_down_volume() {
+        let mut obv = Obv::new();
+        let candles = [
+            (10.0, 5.0),
+            (11.0, 2.0),
+            (11.0, 3.0),
+            (9.0, 4.0),
+            (12.0, 1.0),
+        ];
+        let candles: Vec<_> = candles
+            .iter()
+            .map(|&(close, volume)| ohlcv(close, close, close, close, volume))
+            .collect();
+        let values: Vec<Option<f64>> = candles.iter().map(|c| obv.on_candle(c)).collect();
+
+        assert_eq!(values, [None, Some(2.0), Some(2.0), Some(-2.0), Some(-1.0)]);
+        assert_resets(&mut Obv::new(), &candles);
+    }
+}
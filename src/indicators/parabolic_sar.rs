@@ -0,0 +1,170 @@
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SarValue {
+    pub sar: f64,
+    pub is_long: bool,
+}
+
+// Wilder's parabolic stop and reverse. The stop accelerates towards price by
+// `step` each time a new extreme is made, up to `max_step`.
+#[derive(Debug)]
+pub struct ParabolicSar {
+    step: f64,
+    max_step: f64,
+    acceleration: f64,
+    extreme_point: f64,
+    // (high, low) of the previous two candles, most recent last.
+    prev: Option<(f64, f64)>,
+    prev_prev: Option<(f64, f64)>,
+    prev_close: f64,
+    current: Option<SarValue>,
+}
+
+impl ParabolicSar {
+    pub fn new(step: f64, max_step: f64) -> Self {
+        ParabolicSar {
+            step,
+            max_step,
+            acceleration: step,
+            extreme_point: 0.0,
+            prev: None,
+            prev_prev: None,
+            prev_close: 0.0,
+            current: None,
+        }
+    }
+
+    fn next_value(&mut self, prev: SarValue, candle: &Candlestick) -> SarValue {
+        let (prev_high, prev_low) = self.prev.unwrap_or((candle.high, candle.low));
+        let (prev_prev_high, prev_prev_low) = self.prev_prev.unwrap_or((prev_high, prev_low));
+        let sar = prev.sar + self.acceleration * (self.extreme_point - prev.sar);
+
+        if prev.is_long {
+            // The stop can't sit above either of the last two lows.
+            let sar = sar.min(prev_low).min(prev_prev_low);
+
+            if candle.low < sar {
+                let sar = self.extreme_point;
+                self.extreme_point = candle.low;
+                self.acceleration = self.step;
+                return SarValue {
+                    sar,
+                    is_long: false,
+                };
+            }
+
+            if candle.high > self.extreme_point {
+                self.extreme_point = candle.high;
+                self.acceleration = (self.acceleration + self.step).min(self.max_step);
+            }
+
+            SarValue { sar, is_long: true }
+        } else {
+            let sar = sar.max(prev_high).max(prev_prev_high);
+
+            if candle.high > sar {
+                let sar = self.extreme_point;
+                self.extreme_point = candle.high;
+                self.acceleration = self.step;
+                return SarValue { sar, is_long: true };
+            }
+
+            if candle.low < self.extreme_point {
+                self.extreme_point = candle.low;
+                self.acceleration = (self.acceleration + self.step).min(self.max_step);
+            }
+
+            SarValue {
+                sar,
+                is_long: false,
+            }
+        }
+    }
+}
+
+impl Indicator for ParabolicSar {
+    type Output = SarValue;
+
+    fn warmup_period(&self) -> usize {
+        2
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<SarValue> {
+        match (self.current, self.prev) {
+            (Some(prev), _) => self.current = Some(self.next_value(prev, candle)),
+            // The first trend follows the direction of the first two closes.
+            (None, Some((prev_high, prev_low))) => {
+                let is_long = candle.close >= self.prev_close;
+                self.acceleration = self.step;
+
+                self.current = Some(if is_long {
+                    self.extreme_point = candle.high;
+                    SarValue {
+                        sar: prev_low.min(candle.low),
+                        is_long,
+                    }
+                } else {
+                    self.extreme_point = candle.low;
+                    SarValue {
+                        sar: prev_high.max(candle.high),
+                        is_long,
+                    }
+                });
+            }
+            (None, None) => {}
+        }
+
+        self.prev_prev = self.prev;
+        self.prev = Some((candle.high, candle.low));
+        self.prev_close = candle.close;
+        self.current
+    }
+
+    fn get_value(&self) -> Option<SarValue> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        *self = ParabolicSar::new(self.step, self.max_step);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::{assert_close, ohlcv};
+
+    fn next(sar: &mut ParabolicSar, high: f64, low: f64, close: f64) -> SarValue {
+        sar.on_candle(&ohlcv(close, high, low, close, 0.0)).unwrap()
+    }
+
+    #[test]
+    fn accelerates_and_reverses() {
+        let mut sar = ParabolicSar::new(0.1, 0.2);
+        assert_eq!(sar.on_candle(&ohlcv(9.5, 10.0, 9.0, 9.5, 0.0)), None);
+
+        // A higher close starts long from the lowest of the two lows.
+        let value = next(&mut sar, 11.0, 10.0, 10.5);
+        assert!(value.is_long);
+        assert_close(value.sar, 9.0);
+
+        // 9.2 is held at the low two candles back.
+        assert_close(next(&mut sar, 12.0, 11.0, 11.5).sar, 9.0);
+
+        // The acceleration is capped at 0.2, 9 + 0.2 * (12 - 9).
+        assert_close(next(&mut sar, 13.0, 12.0, 12.5).sar, 9.6);
+
+        // The low goes through 10.28 so it reverses at the extreme point.
+        let value = next(&mut sar, 10.0, 8.0, 8.5);
+        assert!(!value.is_long);
+        assert_close(value.sar, 13.0);
+
+        // 12.5 is held at the high two candles back.
+        let value = next(&mut sar, 9.0, 7.0, 7.5);
+        assert!(!value.is_long);
+        assert_close(value.sar, 13.0);
+    }
+}
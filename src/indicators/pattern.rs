@@ -0,0 +1,369 @@
+use std::collections::VecDeque;
+
+use serde::Deserialize;
+use smallvec::SmallVec;
+
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
+// A body under this share of the candle's range is a doji.
+const DOJI_BODY_RATIO: f64 = 0.1;
+// Hammer and shooting star wicks have to be this many times the body.
+const WICK_BODY_RATIO: f64 = 2.0;
+// The middle candle of a star has a body under this share of the first one.
+const STAR_BODY_RATIO: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pattern {
+    BullishEngulfing,
+    BearishEngulfing,
+    Hammer,
+    ShootingStar,
+    Doji,
+    MorningStar,
+    EveningStar,
+    InsideBar,
+    OutsideBar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternBias {
+    Bullish,
+    Bearish,
+    Neutral,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PatternEvent {
+    pub pattern: Pattern,
+    pub bias: PatternBias,
+    // Between 0.0 and 1.0, how clearly the candles match the pattern.
+    pub strength: f64,
+}
+
+impl PatternEvent {
+    fn new(pattern: Pattern, bias: PatternBias, strength: f64) -> Self {
+        PatternEvent {
+            pattern,
+            bias,
+            strength: strength.clamp(0.0, 1.0),
+        }
+    }
+}
+
+fn body(candle: &Candlestick) -> f64 {
+    (candle.close - candle.open).abs()
+}
+
+fn range(candle: &Candlestick) -> f64 {
+    candle.high - candle.low
+}
+
+fn upper_wick(candle: &Candlestick) -> f64 {
+    candle.high - candle.open.max(candle.close)
+}
+
+fn lower_wick(candle: &Candlestick) -> f64 {
+    candle.open.min(candle.close) - candle.low
+}
+
+fn is_bullish(candle: &Candlestick) -> bool {
+    candle.close > candle.open
+}
+
+fn is_bearish(candle: &Candlestick) -> bool {
+    candle.close < candle.open
+}
+
+// Checks the latest candles for single, two and three candle patterns.
+// Patterns are reported on the candle that completes them, no trend context
+// is applied so callers decide where a hammer or star matters.
+#[derive(Debug, Default)]
+pub struct PatternDetector {
+    candles: VecDeque<Candlestick>,
+    patterns: SmallVec<[PatternEvent; 2]>,
+}
+
+impl PatternDetector {
+    pub fn new() -> Self {
+        PatternDetector::default()
+    }
+
+    // Every pattern completed by the latest candle.
+    pub fn get_patterns(&self) -> &[PatternEvent] {
+        &self.patterns
+    }
+
+    fn detect_single(&mut self, candle: &Candlestick) {
+        let range = range(candle);
+        if range <= 0.0 {
+            return;
+        }
+        let body = body(candle);
+
+        if body <= range * DOJI_BODY_RATIO {
+            let strength = 1.0 - body / (range * DOJI_BODY_RATIO);
+            self.patterns.push(PatternEvent::new(
+                Pattern::Doji,
+                PatternBias::Neutral,
+                strength,
+            ));
+            return;
+        }
+
+        let upper = upper_wick(candle);
+        let lower = lower_wick(candle);
+
+        if lower >= body * WICK_BODY_RATIO && upper <= body {
+            self.patterns.push(PatternEvent::new(
+                Pattern::Hammer,
+                PatternBias::Bullish,
+                lower / range,
+            ));
+        } else if upper >= body * WICK_BODY_RATIO && lower <= body {
+            self.patterns.push(PatternEvent::new(
+                Pattern::ShootingStar,
+                PatternBias::Bearish,
+                upper / range,
+            ));
+        }
+    }
+
+    fn detect_double(&mut self, prev: &Candlestick, candle: &Candlestick) {
+        let prev_body = body(prev);
+        let current_body = body(candle);
+        let body_top = candle.open.max(candle.close);
+        let body_bottom = candle.open.min(candle.close);
+        let prev_top = prev.open.max(prev.close);
+        let prev_bottom = prev.open.min(prev.close);
+
+        if current_body > 0.0
+            && body_top >= prev_top
+            && body_bottom <= prev_bottom
+            && current_body > prev_body
+        {
+            let strength = 1.0 - prev_body / current_body;
+
+            if is_bullish(candle) && is_bearish(prev) {
+                self.patterns.push(PatternEvent::new(
+                    Pattern::BullishEngulfing,
+                    PatternBias::Bullish,
+                    strength,
+                ));
+            } else if is_bearish(candle) && is_bullish(prev) {
+                self.patterns.push(PatternEvent::new(
+                    Pattern::BearishEngulfing,
+                    PatternBias::Bearish,
+                    strength,
+                ));
+            }
+        }
+
+        let prev_range = range(prev);
+        let current_range = range(candle);
+
+        if candle.high < prev.high && candle.low > prev.low && prev_range > 0.0 {
+            self.patterns.push(PatternEvent::new(
+                Pattern::InsideBar,
+                PatternBias::Neutral,
+                1.0 - current_range / prev_range,
+            ));
+        } else if candle.high > prev.high && candle.low < prev.low {
+            let bias = if is_bullish(candle) {
+                PatternBias::Bullish
+            } else if is_bearish(candle) {
+                PatternBias::Bearish
+            } else {
+                PatternBias::Neutral
+            };
+
+            self.patterns.push(PatternEvent::new(
+                Pattern::OutsideBar,
+                bias,
+                1.0 - prev_range / current_range,
+            ));
+        }
+    }
+
+    fn detect_triple(&mut self, first: &Candlestick, star: &Candlestick, candle: &Candlestick) {
+        let first_body = body(first);
+        if first_body <= 0.0 || body(star) > first_body * STAR_BODY_RATIO {
+            return;
+        }
+        let first_midpoint = (first.open + first.close) / 2.0;
+
+        // Strength is how far the third candle closes into the first body.
+        if is_bearish(first) && is_bullish(candle) && candle.close > first_midpoint {
+            self.patterns.push(PatternEvent::new(
+                Pattern::MorningStar,
+                PatternBias::Bullish,
+                (candle.close - first.close) / first_body,
+            ));
+        } else if is_bullish(first) && is_bearish(candle) && candle.close < first_midpoint {
+            self.patterns.push(PatternEvent::new(
+                Pattern::EveningStar,
+                PatternBias::Bearish,
+                (first.close - candle.close) / first_body,
+            ));
+        }
+    }
+}
+
+impl Indicator for PatternDetector {
+    // The strongest pattern completed by the latest candle.
+    type Output = PatternEvent;
+
+    fn warmup_period(&self) -> usize {
+        3
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<PatternEvent> {
+        self.candles.push_back(*candle);
+        if self.candles.len() > 3 {
+            self.candles.pop_front();
+        }
+        self.patterns.clear();
+
+        self.detect_single(candle);
+
+        let len = self.candles.len();
+        if len >= 2 {
+            let prev = self.candles[len - 2];
+            self.detect_double(&prev, candle);
+        }
+        if len == 3 {
+            let (first, star) = (self.candles[0], self.candles[1]);
+            self.detect_triple(&first, &star, candle);
+        }
+
+        self.get_value()
+    }
+
+    fn get_value(&self) -> Option<PatternEvent> {
+        self.patterns
+            .iter()
+            .max_by(|a, b| a.strength.total_cmp(&b.strength))
+            .copied()
+    }
+
+    fn reset(&mut self) {
+        self.candles.clear();
+        self.patterns.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::{assert_close, ohlcv};
+
+    fn candle(open: f64, high: f64, low: f64, close: f64) -> Candlestick {
+        ohlcv(open, high, low, close, 0.0)
+    }
+
+    // Patterns completed by the last candle with their strengths.
+    fn detect(candles: &[Candlestick]) -> Vec<(Pattern, PatternBias, f64)> {
+        let mut detector = PatternDetector::new();
+        for candle in candles {
+            detector.on_candle(candle);
+        }
+
+        detector
+            .get_patterns()
+            .iter()
+            .map(|event| (event.pattern, event.bias, event.strength))
+            .collect()
+    }
+
+    fn assert_patterns(candles: &[Candlestick], expected: &[(Pattern, PatternBias, f64)]) {
+        let patterns = detect(candles);
+        assert_eq!(patterns.len(), expected.len(), "{:?}", patterns);
+
+        for (actual, expected) in patterns.iter().zip(expected) {
+            assert_eq!((actual.0, actual.1), (expected.0, expected.1));
+            assert_close(actual.2, expected.2);
+        }
+    }
+
+    #[test]
+    fn single_candle_patterns() {
+        // Body of 0.1 against the 0.2 allowed for a range of 2.
+        assert_patterns(
+            &[candle(10.0, 11.0, 9.0, 10.1)],
+            &[(Pattern::Doji, PatternBias::Neutral, 0.5)],
+        );
+        assert_patterns(
+            &[candle(10.0, 11.0, 5.0, 11.0)],
+            &[(Pattern::Hammer, PatternBias::Bullish, 5.0 / 6.0)],
+        );
+        assert_patterns(
+            &[candle(11.0, 16.0, 10.0, 10.0)],
+            &[(Pattern::ShootingStar, PatternBias::Bearish, 5.0 / 6.0)],
+        );
+        assert_patterns(&[candle(10.0, 10.0, 10.0, 10.0)], &[]);
+    }
+
+    #[test]
+    fn engulfing_patterns() {
+        // The wider range makes it an outside bar as well.
+        assert_patterns(
+            &[candle(11.0, 11.5, 9.5, 10.0), candle(9.8, 12.0, 9.0, 11.5)],
+            &[
+                (Pattern::BullishEngulfing, PatternBias::Bullish, 0.7 / 1.7),
+                (Pattern::OutsideBar, PatternBias::Bullish, 1.0 / 3.0),
+            ],
+        );
+        // Same high and low, so neither inside nor outside.
+        assert_patterns(
+            &[candle(10.0, 11.2, 9.8, 11.0), candle(11.1, 11.2, 9.8, 9.9)],
+            &[(Pattern::BearishEngulfing, PatternBias::Bearish, 1.0 / 6.0)],
+        );
+    }
+
+    #[test]
+    fn inside_bar() {
+        assert_patterns(
+            &[
+                candle(10.0, 12.0, 9.0, 11.0),
+                candle(10.2, 11.0, 10.0, 10.8),
+            ],
+            &[(Pattern::InsideBar, PatternBias::Neutral, 2.0 / 3.0)],
+        );
+    }
+
+    #[test]
+    fn star_patterns() {
+        let mut detector = PatternDetector::new();
+        assert_eq!(detector.on_candle(&candle(12.0, 12.2, 9.9, 10.0)), None);
+        assert_eq!(detector.on_candle(&candle(9.75, 9.85, 9.25, 9.5)), None);
+
+        // Closes 1.5 into the first body of 2.
+        let event = detector.on_candle(&candle(10.0, 11.6, 9.9, 11.5)).unwrap();
+        assert_eq!(event.pattern, Pattern::MorningStar);
+        assert_close(event.strength, 0.75);
+
+        assert_patterns(
+            &[
+                candle(10.0, 12.1, 9.8, 12.0),
+                candle(12.2, 12.4, 12.1, 12.3),
+                candle(12.0, 12.1, 10.4, 10.5),
+            ],
+            &[(Pattern::EveningStar, PatternBias::Bearish, 0.75)],
+        );
+    }
+
+    #[test]
+    fn large_middle_candle_is_not_a_star() {
+        let patterns = detect(&[
+            candle(12.0, 12.2, 9.9, 10.0),
+            candle(9.5, 10.6, 9.4, 10.5),
+            candle(10.0, 11.6, 9.9, 11.5),
+        ]);
+
+        assert!(patterns
+            .iter()
+            .all(|(pattern, _, _)| *pattern != Pattern::MorningStar));
+    }
+}
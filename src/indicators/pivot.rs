@@ -0,0 +1,205 @@
+use serde::Deserialize;
+
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PivotKind {
+    #[default]
+    Classic,
+    Fibonacci,
+    Camarilla,
+}
+
+// Levels for the current day, the first entry of each side is the closest to the pivot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PivotLevels {
+    pub pivot: f64,
+    pub resistance: [f64; 3],
+    pub support: [f64; 3],
+}
+
+impl PivotLevels {
+    pub fn new(kind: PivotKind, day: &Candlestick) -> Self {
+        let (high, low, close) = (day.high, day.low, day.close);
+        let pivot = (high + low + close) / 3.0;
+        let range = high - low;
+
+        let (resistance, support) = match kind {
+            PivotKind::Classic => (
+                [2.0 * pivot - low, pivot + range, high + 2.0 * (pivot - low)],
+                [
+                    2.0 * pivot - high,
+                    pivot - range,
+                    low - 2.0 * (high - pivot),
+                ],
+            ),
+            PivotKind::Fibonacci => (
+                [pivot + 0.382 * range, pivot + 0.618 * range, pivot + range],
+                [pivot - 0.382 * range, pivot - 0.618 * range, pivot - range],
+            ),
+            // Camarilla levels are built around the close rather than the pivot.
+            PivotKind::Camarilla => (
+                [
+                    close + range * 1.1 / 12.0,
+                    close + range * 1.1 / 6.0,
+                    close + range * 1.1 / 4.0,
+                ],
+                [
+                    close - range * 1.1 / 12.0,
+                    close - range * 1.1 / 6.0,
+                    close - range * 1.1 / 4.0,
+                ],
+            ),
+        };
+
+        PivotLevels {
+            pivot,
+            resistance,
+            support,
+        }
+    }
+
+    pub fn levels(&self) -> [f64; 7] {
+        let [r1, r2, r3] = self.resistance;
+        let [s1, s2, s3] = self.support;
+
+        [self.pivot, r1, r2, r3, s1, s2, s3]
+    }
+}
+
+// Pivot levels from the previous UTC day. The day is built up from the
+// candle stream, or can be set from a daily candle so the levels are ready
+// without waiting for the first day to roll over.
+#[derive(Debug)]
+pub struct PivotPoints {
+    kind: PivotKind,
+    day: Option<i64>,
+    // Running high, low and close of the current day, None when the day
+    // was joined part way through.
+    day_candle: Option<Candlestick>,
+    current: Option<PivotLevels>,
+}
+
+impl PivotPoints {
+    pub fn new(kind: PivotKind) -> Self {
+        PivotPoints {
+            kind,
+            day: None,
+            day_candle: None,
+            current: None,
+        }
+    }
+
+    pub fn set_previous_day(&mut self, day: &Candlestick) {
+        self.current = Some(PivotLevels::new(self.kind, day));
+    }
+}
+
+impl Indicator for PivotPoints {
+    type Output = PivotLevels;
+
+    fn warmup_period(&self) -> usize {
+        1
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<PivotLevels> {
+        let day = candle.start.div_euclid(SECONDS_PER_DAY);
+
+        if self.day != Some(day) {
+            // Only a day that was seen from its first candle gives real levels.
+            if let Some(completed) = self.day_candle.take() {
+                self.set_previous_day(&completed);
+            }
+
+            let joined_at_open = self.day.is_some();
+            self.day = Some(day);
+            self.day_candle = joined_at_open.then_some(*candle);
+        } else if let Some(day_candle) = self.day_candle.as_mut() {
+            day_candle.high = day_candle.high.max(candle.high);
+            day_candle.low = day_candle.low.min(candle.low);
+            day_candle.close = candle.close;
+            day_candle.volume += candle.volume;
+        }
+
+        self.current
+    }
+
+    fn get_value(&self) -> Option<PivotLevels> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        *self = PivotPoints::new(self.kind);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::{assert_close, ohlcv};
+
+    // Pivot 9 and range 6.
+    fn day() -> Candlestick {
+        ohlcv(8.0, 12.0, 6.0, 9.0, 0.0)
+    }
+
+    fn assert_levels(levels: PivotLevels, expected: [f64; 7]) {
+        for (actual, expected) in levels.levels().into_iter().zip(expected) {
+            assert_close(actual, expected);
+        }
+    }
+
+    #[test]
+    fn classic_levels() {
+        let levels = PivotLevels::new(PivotKind::Classic, &day());
+        assert_levels(levels, [9.0, 12.0, 15.0, 18.0, 6.0, 3.0, 0.0]);
+    }
+
+    #[test]
+    fn fibonacci_levels() {
+        let levels = PivotLevels::new(PivotKind::Fibonacci, &day());
+        assert_levels(levels, [9.0, 11.292, 12.708, 15.0, 6.708, 5.292, 3.0]);
+    }
+
+    #[test]
+    fn camarilla_levels() {
+        // Steps of 6.6 / 12, 6.6 / 6 and 6.6 / 4 either side of the close.
+        let levels = PivotLevels::new(PivotKind::Camarilla, &day());
+        assert_levels(levels, [9.0, 9.55, 10.1, 10.65, 8.45, 7.9, 7.35]);
+    }
+
+    #[test]
+    fn waits_for_a_whole_day() {
+        let mut pivots = PivotPoints::new(PivotKind::Classic);
+        let candle = |start, high, low, close| Candlestick {
+            start,
+            ..ohlcv(close, high, low, close, 0.0)
+        };
+
+        // The first day was joined part way through so it gives no levels.
+        assert_eq!(pivots.on_candle(&candle(3_600, 20.0, 1.0, 10.0)), None);
+        assert_eq!(
+            pivots.on_candle(&candle(SECONDS_PER_DAY, 10.0, 6.0, 8.0)),
+            None
+        );
+        assert_eq!(
+            pivots.on_candle(&candle(SECONDS_PER_DAY + 3_600, 12.0, 7.0, 9.0)),
+            None
+        );
+
+        let levels = pivots
+            .on_candle(&candle(2 * SECONDS_PER_DAY, 9.0, 9.0, 9.0))
+            .unwrap();
+        assert_eq!(levels, PivotLevels::new(PivotKind::Classic, &day()));
+
+        pivots.reset();
+        assert_eq!(pivots.get_value(), None);
+        pivots.set_previous_day(&day());
+        assert_eq!(pivots.get_value().map(|levels| levels.pivot), Some(9.0));
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Extreme {
+    Max,
+    Min,
+}
+
+// Highest or lowest value of the last `period` inputs in amortised O(1),
+// using a deque of candidates that are kept in order.
+#[derive(Debug)]
+pub struct RollingExtreme {
+    period: usize,
+    extreme: Extreme,
+    count: usize,
+    candidates: VecDeque<(usize, f64)>,
+}
+
+impl RollingExtreme {
+    pub fn max(period: usize) -> Self {
+        RollingExtreme::new(period, Extreme::Max)
+    }
+
+    pub fn min(period: usize) -> Self {
+        RollingExtreme::new(period, Extreme::Min)
+    }
+
+    fn new(period: usize, extreme: Extreme) -> Self {
+        RollingExtreme {
+            period,
+            extreme,
+            count: 0,
+            candidates: VecDeque::with_capacity(period),
+        }
+    }
+
+    pub fn update(&mut self, value: f64) -> Option<f64> {
+        // Drop values that can never be the extreme again.
+        while let Some(&(_, last)) = self.candidates.back() {
+            let dominated = match self.extreme {
+                Extreme::Max => last <= value,
+                Extreme::Min => last >= value,
+            };
+
+            if dominated {
+                self.candidates.pop_back();
+            } else {
+                break;
+            }
+        }
+
+        self.candidates.push_back((self.count, value));
+        self.count += 1;
+
+        // Drop the front once it falls out of the window.
+        while let Some(&(index, _)) = self.candidates.front() {
+            if index + self.period < self.count {
+                self.candidates.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        self.get_value()
+    }
+
+    // None until `period` values have been seen.
+    pub fn get_value(&self) -> Option<f64> {
+        if self.count < self.period {
+            return None;
+        }
+
+        self.candidates.front().map(|&(_, value)| value)
+    }
+
+    pub fn reset(&mut self) {
+        self.count = 0;
+        self.candidates.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALUES: [f64; 8] = [3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0, 6.0];
+
+    #[test]
+    fn rolling_max() {
+        let mut highest = RollingExtreme::max(3);
+        let values: Vec<Option<f64>> = VALUES.iter().map(|&v| highest.update(v)).collect();
+
+        assert_eq!(
+            values,
+            [
+                None,
+                None,
+                Some(4.0),
+                Some(4.0),
+                Some(5.0),
+                Some(9.0),
+                Some(9.0),
+                Some(9.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn rolling_min() {
+        let mut lowest = RollingExtreme::min(3);
+        let values: Vec<Option<f64>> = VALUES.iter().map(|&v| lowest.update(v)).collect();
+
+        assert_eq!(
+            values,
+            [
+                None,
+                None,
+                Some(1.0),
+                Some(1.0),
+                Some(1.0),
+                Some(1.0),
+                Some(2.0),
+                Some(2.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_a_full_scan() {
+        let mut highest = RollingExtreme::max(4);
+        let mut lowest = RollingExtreme::min(4);
+        let values: Vec<f64> = (0..50).map(|i| ((i * 37) % 23) as f64).collect();
+
+        for (i, &value) in values.iter().enumerate() {
+            highest.update(value);
+            lowest.update(value);
+
+            if i >= 3 {
+                let window = &values[i - 3..=i];
+                assert_eq!(highest.get_value(), window.iter().cloned().reduce(f64::max));
+                assert_eq!(lowest.get_value(), window.iter().cloned().reduce(f64::min));
+            }
+        }
+    }
+}
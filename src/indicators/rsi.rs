@@ -1,3 +1,7 @@
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
 #[derive(Debug)]
 pub struct Rsi {
     period: usize,
@@ -57,10 +61,24 @@ impl Rsi {
             self.current_rsi
         }
     }
+}
+
+impl Indicator for Rsi {
+    type Output = f64;
+
+    fn warmup_period(&self) -> usize {
+        self.period + 1
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<f64> {
+        self.update(candle.close)
+    }
 
-    #[inline]
-    // Method to retrieve the current RSI value
-    pub fn get_current_rsi(&self) -> Option<f64> {
+    fn get_value(&self) -> Option<f64> {
         self.current_rsi
     }
+
+    fn reset(&mut self) {
+        *self = Rsi::new(self.period);
+    }
 }
@@ -0,0 +1,84 @@
+use std::collections::VecDeque;
+
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
+#[derive(Debug)]
+pub struct Sma {
+    period: usize,
+    values: VecDeque<f64>,
+    sum: f64,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        Sma {
+            period,
+            values: VecDeque::with_capacity(period),
+            sum: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, price: f64) -> Option<f64> {
+        self.values.push_back(price);
+        self.sum += price;
+
+        if self.values.len() > self.period {
+            self.sum -= self.values.pop_front().unwrap_or_default();
+        }
+
+        self.get_value()
+    }
+}
+
+impl Indicator for Sma {
+    type Output = f64;
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<f64> {
+        self.update(candle.close)
+    }
+
+    fn get_value(&self) -> Option<f64> {
+        if self.values.len() == self.period {
+            Some(self.sum / self.period as f64)
+        } else {
+            None
+        }
+    }
+
+    fn reset(&mut self) {
+        self.values.clear();
+        self.sum = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::{assert_close, assert_resets, ohlcv};
+
+    #[test]
+    fn averages_the_last_period_values() {
+        let mut sma = Sma::new(3);
+
+        assert_eq!(sma.update(1.0), None);
+        assert_eq!(sma.update(2.0), None);
+        assert_close(sma.update(3.0).unwrap(), 2.0);
+        assert_close(sma.update(4.0).unwrap(), 3.0);
+        assert_close(sma.update(11.0).unwrap(), 6.0);
+    }
+
+    #[test]
+    fn reset_starts_over() {
+        let candles: Vec<_> = [10.0, 20.0, 4.0, 6.0]
+            .iter()
+            .map(|&close| ohlcv(close, close, close, close, 0.0))
+            .collect();
+        assert_resets(&mut Sma::new(2), &candles);
+    }
+}
@@ -1,67 +1,164 @@
-use std::collections::VecDeque;
+use crate::model::event::Candlestick;
 
-struct StochasticOscillator {
-    period: usize,
-    prices: VecDeque<f64>,
-    stoch_values: VecDeque<(f64, f64)>,
+use super::{rolling::RollingExtreme, sma::Sma, Indicator};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StochasticValue {
+    pub k: f64,
+    pub d: f64,
+}
+
+// Stochastic oscillator from candle high, low and close.
+// %K is smoothed over `k_smoothing` candles (1 for the fast stochastic)
+// and %D is the moving average of the smoothed %K.
+#[derive(Debug)]
+pub struct Stochastic {
+    k_period: usize,
+    k_smoothing: usize,
+    d_period: usize,
+    highest: RollingExtreme,
+    lowest: RollingExtreme,
+    k_sma: Sma,
+    d_sma: Sma,
+    current: Option<StochasticValue>,
 }
 
-impl StochasticOscillator {
-    fn new(period: usize) -> Self {
-        StochasticOscillator {
-            period,
-            prices: VecDeque::with_capacity(period),
-            stoch_values: VecDeque::new(),
+impl Stochastic {
+    pub fn new(k_period: usize, k_smoothing: usize, d_period: usize) -> Self {
+        Stochastic {
+            k_period,
+            k_smoothing,
+            d_period,
+            highest: RollingExtreme::max(k_period),
+            lowest: RollingExtreme::min(k_period),
+            k_sma: Sma::new(k_smoothing.max(1)),
+            d_sma: Sma::new(d_period.max(1)),
+            current: None,
         }
     }
 
-    fn update(&mut self, price: f64) -> Option<(f64, f64)> {
-        if self.prices.len() == self.period {
-            self.prices.pop_front();
-        }
+    pub fn update(&mut self, high: f64, low: f64, close: f64) -> Option<StochasticValue> {
+        let (highest, lowest) = match (self.highest.update(high), self.lowest.update(low)) {
+            (Some(highest), Some(lowest)) => (highest, lowest),
+            _ => return None,
+        };
 
-        self.prices.push_back(price);
+        // A flat range has no position within it, use the middle.
+        let raw_k = if highest > lowest {
+            (close - lowest) / (highest - lowest) * 100.0
+        } else {
+            50.0
+        };
 
-        if self.prices.len() < self.period {
-            return None;
+        if let Some(k) = self.k_sma.update(raw_k) {
+            if let Some(d) = self.d_sma.update(k) {
+                self.current = Some(StochasticValue { k, d });
+            }
         }
 
-        let high = *self
-            .prices
-            .iter()
-            .max_by(|x, y| x.partial_cmp(y).unwrap())
-            .unwrap();
-        let low = *self
-            .prices
-            .iter()
-            .min_by(|x, y| x.partial_cmp(y).unwrap())
-            .unwrap();
+        self.current
+    }
+}
 
-        let k = (price - low) / (high - low) * 100.0;
+impl Indicator for Stochastic {
+    type Output = StochasticValue;
 
-        let d = if self.stoch_values.len() < 3 {
-            k // If we don't have enough values, just set %D to %K
-        } else {
-            let sum: f64 = self.stoch_values.iter().rev().take(3).map(|(k, _)| k).sum();
-            sum / 3.0
-        };
+    fn warmup_period(&self) -> usize {
+        self.k_period + self.k_smoothing.max(1) + self.d_period.max(1) - 2
+    }
 
-        let new_values = (k, d);
-        self.stoch_values.push_back(new_values);
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<StochasticValue> {
+        self.update(candle.high, candle.low, candle.close)
+    }
+
+    fn get_value(&self) -> Option<StochasticValue> {
+        self.current
+    }
 
-        Some(new_values)
+    fn reset(&mut self) {
+        self.highest.reset();
+        self.lowest.reset();
+        self.k_sma.reset();
+        self.d_sma.reset();
+        self.current = None;
     }
 }
 
-fn main() {
-    let mut stoch = StochasticOscillator::new(5);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::{assert_close, assert_resets, ohlcv};
 
-    let incoming_data = vec![
-        45.0, 46.0, 47.0, 48.5, 50.5, 52.0, 53.0, 55.0, 57.0, 56.0, 55.5, 54.0,
+    // High, low and close, the expected values are worked through by hand
+    // from %K = (close - lowest low) / (highest high - lowest low).
+    const SERIES: [(f64, f64, f64); 6] = [
+        (10.0, 8.0, 9.0),
+        (11.0, 9.0, 10.0),
+        (12.0, 10.0, 11.0),
+        (13.0, 11.0, 13.0),
+        (12.0, 9.0, 10.0),
+        (11.0, 8.0, 8.0),
     ];
-    for price in incoming_data {
-        if let Some((k, d)) = stoch.update(price) {
-            println!("K: {:.2}, D: {:.2}", k, d);
+
+    fn candles() -> Vec<Candlestick> {
+        SERIES
+            .iter()
+            .map(|&(high, low, close)| ohlcv(close, high, low, close, 0.0))
+            .collect()
+    }
+
+    fn feed(stochastic: &mut Stochastic) -> Vec<Option<StochasticValue>> {
+        candles().iter().map(|c| stochastic.on_candle(c)).collect()
+    }
+
+    #[test]
+    fn fast_stochastic() {
+        let mut stochastic = Stochastic::new(3, 1, 3);
+        let values = feed(&mut stochastic);
+
+        // Raw %K runs 75, 100, 25, 0 from the third candle.
+        assert!(values[..4].iter().all(Option::is_none));
+        assert_eq!(stochastic.warmup_period(), 5);
+
+        let value = values[4].unwrap();
+        assert_close(value.k, 25.0);
+        assert_close(value.d, 200.0 / 3.0);
+
+        let value = values[5].unwrap();
+        assert_close(value.k, 0.0);
+        assert_close(value.d, 125.0 / 3.0);
+    }
+
+    #[test]
+    fn slow_stochastic() {
+        let mut stochastic = Stochastic::new(3, 2, 2);
+        let values = feed(&mut stochastic);
+
+        // Smoothed %K runs 87.5, 62.5, 12.5.
+        assert!(values[..4].iter().all(Option::is_none));
+
+        let value = values[4].unwrap();
+        assert_close(value.k, 62.5);
+        assert_close(value.d, 75.0);
+
+        let value = values[5].unwrap();
+        assert_close(value.k, 12.5);
+        assert_close(value.d, 37.5);
+    }
+
+    #[test]
+    fn flat_range_is_midpoint() {
+        let mut stochastic = Stochastic::new(3, 1, 1);
+        let mut value = None;
+        for _ in 0..3 {
+            value = stochastic.on_candle(&ohlcv(5.0, 5.0, 5.0, 5.0, 0.0));
         }
+
+        assert_eq!(value, Some(StochasticValue { k: 50.0, d: 50.0 }));
+    }
+
+    #[test]
+    fn reset_starts_over() {
+        assert_resets(&mut Stochastic::new(3, 2, 2), &candles());
     }
 }
@@ -1,7 +1,17 @@
-use super::rsi::Rsi;
+use crate::model::event::Candlestick;
 
+use super::{rsi::Rsi, Indicator};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StochRsiValue {
+    pub k: f64,
+    pub d: f64,
+}
+
+#[derive(Debug)]
 pub struct StochRsi {
     rsi: Rsi,
+    rsi_period: usize,
     period: usize,   // for StochRSI
     d_period: usize, // for the D line
     rsi_values: Vec<f64>,
@@ -14,6 +24,7 @@ impl StochRsi {
     pub fn new(rsi_period: usize, stoch_rsi_period: usize, d_period: usize) -> Self {
         StochRsi {
             rsi: Rsi::new(rsi_period),
+            rsi_period,
             period: stoch_rsi_period,
             d_period,
             rsi_values: Vec::with_capacity(stoch_rsi_period),
@@ -23,7 +34,7 @@ impl StochRsi {
         }
     }
 
-    pub fn update(&mut self, price: f64) -> (Option<f64>, Option<f64>) {
+    pub fn update(&mut self, price: f64) -> Option<StochRsiValue> {
         if let Some(rsi_value) = self.rsi.update(price) {
             self.rsi_values.push(rsi_value);
 
@@ -58,16 +69,29 @@ impl StochRsi {
             }
         }
 
-        (self.current_stoch_rsi, self.current_d)
+        self.get_value()
+    }
+}
+
+impl Indicator for StochRsi {
+    type Output = StochRsiValue;
+
+    fn warmup_period(&self) -> usize {
+        self.rsi.warmup_period() + self.period + self.d_period - 2
     }
 
-    #[inline]
-    pub fn get_current_k(&self) -> Option<f64> {
-        self.current_stoch_rsi
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<StochRsiValue> {
+        self.update(candle.close)
+    }
+
+    fn get_value(&self) -> Option<StochRsiValue> {
+        match (self.current_stoch_rsi, self.current_d) {
+            (Some(k), Some(d)) => Some(StochRsiValue { k, d }),
+            _ => None,
+        }
     }
 
-    #[inline]
-    pub fn get_current_d(&self) -> Option<f64> {
-        self.current_d
+    fn reset(&mut self) {
+        *self = StochRsi::new(self.rsi_period, self.period, self.d_period);
     }
 }
@@ -0,0 +1,143 @@
+use crate::model::event::Candlestick;
+
+use super::{atr::Atr, Indicator};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SuperTrendValue {
+    // Lower band in an uptrend, upper band in a downtrend.
+    pub line: f64,
+    pub is_uptrend: bool,
+}
+
+// Atr bands around the candle midpoint that only move in the trend's
+// direction, the trend flips when price closes through the band.
+#[derive(Debug)]
+pub struct SuperTrend {
+    atr: Atr,
+    multiplier: f64,
+    prev_close: f64,
+    upper: f64,
+    lower: f64,
+    current: Option<SuperTrendValue>,
+}
+
+impl SuperTrend {
+    pub fn new(atr_period: usize, multiplier: f64) -> Self {
+        SuperTrend {
+            atr: Atr::new(atr_period),
+            multiplier,
+            prev_close: 0.0,
+            upper: 0.0,
+            lower: 0.0,
+            current: None,
+        }
+    }
+}
+
+impl Indicator for SuperTrend {
+    type Output = SuperTrendValue;
+
+    fn warmup_period(&self) -> usize {
+        self.atr.warmup_period()
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<SuperTrendValue> {
+        let atr = match self.atr.on_candle(candle) {
+            Some(atr) => atr,
+            None => {
+                self.prev_close = candle.close;
+                return None;
+            }
+        };
+
+        let midpoint = (candle.high + candle.low) / 2.0;
+        let basic_upper = midpoint + self.multiplier * atr;
+        let basic_lower = midpoint - self.multiplier * atr;
+
+        let value = match self.current {
+            Some(prev) => {
+                if basic_upper < self.upper || self.prev_close > self.upper {
+                    self.upper = basic_upper;
+                }
+                if basic_lower > self.lower || self.prev_close < self.lower {
+                    self.lower = basic_lower;
+                }
+
+                let is_uptrend = if prev.is_uptrend {
+                    candle.close >= self.lower
+                } else {
+                    candle.close > self.upper
+                };
+
+                SuperTrendValue {
+                    line: if is_uptrend { self.lower } else { self.upper },
+                    is_uptrend,
+                }
+            }
+            None => {
+                self.upper = basic_upper;
+                self.lower = basic_lower;
+                let is_uptrend = candle.close >= midpoint;
+
+                SuperTrendValue {
+                    line: if is_uptrend { self.lower } else { self.upper },
+                    is_uptrend,
+                }
+            }
+        };
+
+        self.prev_close = candle.close;
+        self.current = Some(value);
+        self.current
+    }
+
+    fn get_value(&self) -> Option<SuperTrendValue> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        self.atr.reset();
+        self.prev_close = 0.0;
+        self.upper = 0.0;
+        self.lower = 0.0;
+        self.current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::{assert_close, ohlcv};
+
+    fn next(supertrend: &mut SuperTrend, high: f64, low: f64, close: f64) -> SuperTrendValue {
+        supertrend
+            .on_candle(&ohlcv(close, high, low, close, 0.0))
+            .unwrap()
+    }
+
+    #[test]
+    fn bands_ratchet_and_flip() {
+        let mut supertrend = SuperTrend::new(2, 1.0);
+        assert_eq!(supertrend.on_candle(&ohlcv(9.0, 10.0, 8.0, 9.0, 0.0)), None);
+
+        // Atr 2.5 around a midpoint of 11, closing over the midpoint.
+        let value = next(&mut supertrend, 12.0, 10.0, 11.0);
+        assert!(value.is_uptrend);
+        assert_close(value.line, 8.5);
+
+        // The lower band only rises, the upper band only falls.
+        let value = next(&mut supertrend, 14.0, 12.0, 13.0);
+        assert!(value.is_uptrend);
+        assert_close(value.line, 10.25);
+
+        // Closing under the lower band flips to the upper band at 13.5.
+        let value = next(&mut supertrend, 12.0, 8.0, 9.0);
+        assert!(!value.is_uptrend);
+        assert_close(value.line, 13.5);
+
+        // Atr 2.9375 around 9 pulls the upper band down.
+        let value = next(&mut supertrend, 10.0, 8.0, 9.0);
+        assert!(!value.is_uptrend);
+        assert_close(value.line, 11.9375);
+    }
+}
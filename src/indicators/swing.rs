@@ -0,0 +1,179 @@
+use std::collections::VecDeque;
+
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwingKind {
+    High,
+    Low,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwingPoint {
+    pub kind: SwingKind,
+    pub price: f64,
+}
+
+// Swing highs and lows are candles with `strength` lower highs, or higher
+// lows, on both sides. They are confirmed `strength` candles after they form
+// and the most recent `count` of each are kept.
+#[derive(Debug)]
+pub struct SwingPoints {
+    strength: usize,
+    count: usize,
+    window: VecDeque<(f64, f64)>,
+    highs: VecDeque<f64>,
+    lows: VecDeque<f64>,
+    current: Option<SwingPoint>,
+}
+
+impl SwingPoints {
+    pub fn new(strength: usize, count: usize) -> Self {
+        SwingPoints {
+            strength,
+            count,
+            window: VecDeque::with_capacity(strength * 2 + 1),
+            highs: VecDeque::with_capacity(count),
+            lows: VecDeque::with_capacity(count),
+            current: None,
+        }
+    }
+
+    // Newest first.
+    pub fn get_highs(&self) -> impl Iterator<Item = f64> + '_ {
+        self.highs.iter().rev().copied()
+    }
+
+    pub fn get_lows(&self) -> impl Iterator<Item = f64> + '_ {
+        self.lows.iter().rev().copied()
+    }
+
+    fn push_level(levels: &mut VecDeque<f64>, count: usize, price: f64) {
+        levels.push_back(price);
+        if levels.len() > count {
+            levels.pop_front();
+        }
+    }
+}
+
+impl Indicator for SwingPoints {
+    type Output = SwingPoint;
+
+    fn warmup_period(&self) -> usize {
+        self.strength * 2 + 1
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<SwingPoint> {
+        self.window.push_back((candle.high, candle.low));
+        if self.window.len() > self.strength * 2 + 1 {
+            self.window.pop_front();
+        }
+        if self.window.len() < self.strength * 2 + 1 {
+            return None;
+        }
+
+        let (high, low) = self.window[self.strength];
+        let others = || {
+            self.window
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != self.strength)
+                .map(|(_, level)| *level)
+        };
+
+        let is_swing_high = others().all(|(other_high, _)| other_high < high);
+        let is_swing_low = others().all(|(_, other_low)| other_low > low);
+
+        if is_swing_high {
+            Self::push_level(&mut self.highs, self.count, high);
+            self.current = Some(SwingPoint {
+                kind: SwingKind::High,
+                price: high,
+            });
+        }
+        if is_swing_low {
+            Self::push_level(&mut self.lows, self.count, low);
+            self.current = Some(SwingPoint {
+                kind: SwingKind::Low,
+                price: low,
+            });
+        }
+
+        (is_swing_high || is_swing_low)
+            .then_some(self.current)
+            .flatten()
+    }
+
+    // The most recently confirmed swing point.
+    fn get_value(&self) -> Option<SwingPoint> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.highs.clear();
+        self.lows.clear();
+        self.current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::ohlcv;
+
+    fn next(swings: &mut SwingPoints, high: f64, low: f64) -> Option<SwingPoint> {
+        swings.on_candle(&ohlcv(low, high, low, high, 0.0))
+    }
+
+    fn point(kind: SwingKind, price: f64) -> Option<SwingPoint> {
+        Some(SwingPoint { kind, price })
+    }
+
+    #[test]
+    fn confirms_swings_after_strength_candles() {
+        let mut swings = SwingPoints::new(1, 2);
+        let values: Vec<Option<SwingPoint>> = [
+            (10.0, 8.0),
+            (12.0, 9.0),
+            (11.0, 7.0),
+            (13.0, 10.0),
+            (12.0, 11.0),
+            (14.0, 12.0),
+            (12.0, 5.0),
+        ]
+        .iter()
+        .map(|&(high, low)| next(&mut swings, high, low))
+        .collect();
+
+        assert_eq!(
+            values,
+            [
+                None,
+                None,
+                point(SwingKind::High, 12.0),
+                point(SwingKind::Low, 7.0),
+                point(SwingKind::High, 13.0),
+                None,
+                point(SwingKind::High, 14.0),
+            ]
+        );
+
+        // Only the newest two highs are kept.
+        assert_eq!(swings.get_highs().collect::<Vec<_>>(), [14.0, 13.0]);
+        assert_eq!(swings.get_lows().collect::<Vec<_>>(), [7.0]);
+        assert_eq!(swings.get_value(), point(SwingKind::High, 14.0));
+    }
+
+    #[test]
+    fn equal_highs_are_not_a_swing() {
+        let mut swings = SwingPoints::new(1, 2);
+        next(&mut swings, 10.0, 8.0);
+        next(&mut swings, 12.0, 9.0);
+
+        assert_eq!(next(&mut swings, 12.0, 9.0), None);
+        assert_eq!(swings.get_highs().count(), 0);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::model::event::Candlestick;
+
+use super::{ema::Ema, Indicator};
+
+// Triple ema, 3 * ema - 3 * ema(ema) + ema(ema(ema)).
+#[derive(Debug)]
+pub struct Tema {
+    first: Ema,
+    second: Ema,
+    third: Ema,
+    current: Option<f64>,
+}
+
+impl Tema {
+    pub fn new(period: usize) -> Self {
+        Tema {
+            first: Ema::new(period),
+            second: Ema::new(period),
+            third: Ema::new(period),
+            current: None,
+        }
+    }
+
+    pub fn update(&mut self, price: f64) -> Option<f64> {
+        if let Some(first) = self.first.update(price) {
+            if let Some(second) = self.second.update(first) {
+                if let Some(third) = self.third.update(second) {
+                    self.current = Some(3.0 * first - 3.0 * second + third);
+                }
+            }
+        }
+
+        self.current
+    }
+}
+
+impl Indicator for Tema {
+    type Output = f64;
+
+    fn warmup_period(&self) -> usize {
+        self.first.warmup_period() * 3 - 2
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<f64> {
+        self.update(candle.close)
+    }
+
+    fn get_value(&self) -> Option<f64> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        self.first.reset();
+        self.second.reset();
+        self.third.reset();
+        self.current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::assert_close;
+
+    #[test]
+    fn combines_three_levels_of_ema() {
+        let mut tema = Tema::new(2);
+        assert_eq!(tema.warmup_period(), 4);
+
+        // The three emas are 14/3, 34/9 and 26/9 on the fourth price, and
+        // 38/9, 110/27 and 298/81 on the fifth.
+        assert_eq!(tema.update(1.0), None);
+        assert_eq!(tema.update(3.0), None);
+        assert_eq!(tema.update(2.0), None);
+        assert_close(tema.update(6.0).unwrap(), 50.0 / 9.0);
+        assert_close(tema.update(4.0).unwrap(), 334.0 / 81.0);
+    }
+
+    #[test]
+    fn is_built_from_nested_emas() {
+        let mut tema = Tema::new(4);
+        let mut first = Ema::new(4);
+        let mut second = Ema::new(4);
+        let mut third = Ema::new(4);
+
+        for i in 0..40 {
+            let price = 50.0 + ((i * 5) % 9) as f64;
+            let value = tema.update(price);
+
+            let nested = first.update(price).and_then(|e1| {
+                let e2 = second.update(e1)?;
+                let e3 = third.update(e2)?;
+                Some(3.0 * e1 - 3.0 * e2 + e3)
+            });
+
+            match nested {
+                Some(expected) => assert_close(value.unwrap(), expected),
+                None => assert_eq!(value, None),
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::fmt::Debug;
+
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
+// Candle for tests, the start is left at 0 for indicators that ignore it.
+pub fn ohlcv(open: f64, high: f64, low: f64, close: f64, volume: f64) -> Candlestick {
+    Candlestick {
+        start: 0,
+        low,
+        high,
+        open,
+        close,
+        volume,
+    }
+}
+
+pub fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-6,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+// Feeds the candles, resets and feeds them again, a reset indicator has to
+// give exactly the same readings as a fresh one.
+pub fn assert_resets<I>(indicator: &mut I, candles: &[Candlestick])
+where
+    I: Indicator,
+    I::Output: PartialEq + Debug,
+{
+    let first: Vec<_> = candles.iter().map(|c| indicator.on_candle(c)).collect();
+    assert!(
+        first.iter().any(Option::is_some),
+        "the candles never warm the indicator up"
+    );
+
+    indicator.reset();
+    assert_eq!(indicator.get_value(), None);
+
+    let second: Vec<_> = candles.iter().map(|c| indicator.on_candle(c)).collect();
+    assert_eq!(second, first);
+}
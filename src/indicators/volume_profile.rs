@@ -0,0 +1,177 @@
+use std::collections::VecDeque;
+
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolumeProfileValue {
+    // Middle of the price bin with the most volume.
+    pub point_of_control: f64,
+    pub value_area_high: f64,
+    pub value_area_low: f64,
+}
+
+// Volume traded at each price over the last `period` candles. Each candle's
+// volume is spread evenly over the bins its high to low range covers.
+#[derive(Debug)]
+pub struct VolumeProfile {
+    period: usize,
+    bins: usize,
+    // Share of the total volume the value area covers, usually 0.7.
+    value_area: f64,
+    candles: VecDeque<(f64, f64, f64)>,
+    current: Option<VolumeProfileValue>,
+}
+
+impl VolumeProfile {
+    pub fn new(period: usize, bins: usize, value_area: f64) -> Self {
+        VolumeProfile {
+            period,
+            bins: bins.max(1),
+            value_area,
+            candles: VecDeque::with_capacity(period),
+            current: None,
+        }
+    }
+
+    fn calculate(&self) -> Option<VolumeProfileValue> {
+        let low = self
+            .candles
+            .iter()
+            .map(|&(_, low, _)| low)
+            .fold(f64::INFINITY, f64::min);
+        let high = self
+            .candles
+            .iter()
+            .map(|&(high, _, _)| high)
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        if high <= low {
+            return None;
+        }
+
+        let bin_size = (high - low) / self.bins as f64;
+        let bin_index = |price: f64| (((price - low) / bin_size) as usize).min(self.bins - 1);
+        let mut volumes = vec![0.0; self.bins];
+
+        for &(candle_high, candle_low, volume) in self.candles.iter() {
+            let first = bin_index(candle_low);
+            let last = bin_index(candle_high);
+            let share = volume / (last - first + 1) as f64;
+
+            for bin in volumes[first..=last].iter_mut() {
+                *bin += share;
+            }
+        }
+
+        let total: f64 = volumes.iter().sum();
+        let poc = volumes
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(index, _)| index)?;
+
+        // Grow the value area from the point of control towards the busier side.
+        let (mut area_low, mut area_high) = (poc, poc);
+        let mut area_volume = volumes[poc];
+
+        while area_volume < total * self.value_area {
+            let below = area_low.checked_sub(1).map(|i| volumes[i]);
+            let above = volumes.get(area_high + 1).copied();
+
+            match (below, above) {
+                (Some(b), Some(a)) if b > a => {
+                    area_low -= 1;
+                    area_volume += b;
+                }
+                (_, Some(a)) => {
+                    area_high += 1;
+                    area_volume += a;
+                }
+                (Some(b), None) => {
+                    area_low -= 1;
+                    area_volume += b;
+                }
+                (None, None) => break,
+            }
+        }
+
+        Some(VolumeProfileValue {
+            point_of_control: low + (poc as f64 + 0.5) * bin_size,
+            value_area_high: low + (area_high + 1) as f64 * bin_size,
+            value_area_low: low + area_low as f64 * bin_size,
+        })
+    }
+}
+
+impl Indicator for VolumeProfile {
+    type Output = VolumeProfileValue;
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<VolumeProfileValue> {
+        self.candles
+            .push_back((candle.high, candle.low, candle.volume));
+
+        if self.candles.len() > self.period {
+            self.candles.pop_front();
+        }
+
+        if self.candles.len() == self.period {
+            self.current = self.calculate();
+        }
+
+        self.current
+    }
+
+    fn get_value(&self) -> Option<VolumeProfileValue> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        self.candles.clear();
+        self.current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::{assert_close, ohlcv};
+
+    fn next(
+        profile: &mut VolumeProfile,
+        high: f64,
+        low: f64,
+        volume: f64,
+    ) -> Option<VolumeProfileValue> {
+        profile.on_candle(&ohlcv(low, high, low, low, volume))
+    }
+
+    #[test]
+    fn builds_the_value_area_around_the_point_of_control() {
+        let mut profile = VolumeProfile::new(2, 4, 0.9);
+        assert_eq!(next(&mut profile, 14.0, 10.0, 4.0), None);
+
+        // Bins of 1 from 10 hold 1, 7, 1 and 1, ties grow the area upwards.
+        let value = next(&mut profile, 11.5, 11.0, 6.0).unwrap();
+        assert_close(value.point_of_control, 11.5);
+        assert_close(value.value_area_low, 11.0);
+        assert_close(value.value_area_high, 14.0);
+
+        // Bins of 0.75 from 11 hold 6, 0, 1 and 1 once the first candle drops.
+        let value = next(&mut profile, 14.0, 13.0, 2.0).unwrap();
+        assert_close(value.point_of_control, 11.375);
+        assert_close(value.value_area_low, 11.0);
+        assert_close(value.value_area_high, 14.0);
+    }
+
+    #[test]
+    fn needs_a_price_range() {
+        let mut profile = VolumeProfile::new(1, 4, 0.7);
+        assert_eq!(next(&mut profile, 10.0, 10.0, 5.0), None);
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::VecDeque;
+
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VwapValue {
+    pub vwap: f64,
+    pub upper: f64,
+    pub lower: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VwapPeriod {
+    // Resets at the start of each UTC day.
+    Session,
+    // Covers the last n candles.
+    Rolling(usize),
+}
+
+// Volume weighted average of the typical price with bands `multiplier`
+// volume weighted standard deviations away.
+#[derive(Debug)]
+pub struct Vwap {
+    period: VwapPeriod,
+    multiplier: f64,
+    session: Option<i64>,
+    // (price * volume, price^2 * volume, volume) per candle for the rolling window.
+    window: VecDeque<(f64, f64, f64)>,
+    price_volume: f64,
+    price_squared_volume: f64,
+    volume: f64,
+    current: Option<VwapValue>,
+}
+
+impl Vwap {
+    pub fn new(period: VwapPeriod, multiplier: f64) -> Self {
+        Vwap {
+            period,
+            multiplier,
+            session: None,
+            window: VecDeque::new(),
+            price_volume: 0.0,
+            price_squared_volume: 0.0,
+            volume: 0.0,
+            current: None,
+        }
+    }
+
+    fn clear_sums(&mut self) {
+        self.window.clear();
+        self.price_volume = 0.0;
+        self.price_squared_volume = 0.0;
+        self.volume = 0.0;
+    }
+}
+
+impl Indicator for Vwap {
+    type Output = VwapValue;
+
+    fn warmup_period(&self) -> usize {
+        match self.period {
+            VwapPeriod::Session => 1,
+            VwapPeriod::Rolling(period) => period,
+        }
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<VwapValue> {
+        if self.period == VwapPeriod::Session {
+            let session = candle.start.div_euclid(SECONDS_PER_DAY);
+            if self.session != Some(session) {
+                self.session = Some(session);
+                self.clear_sums();
+            }
+        }
+
+        let typical_price = (candle.high + candle.low + candle.close) / 3.0;
+        let entry = (
+            typical_price * candle.volume,
+            typical_price * typical_price * candle.volume,
+            candle.volume,
+        );
+
+        self.window.push_back(entry);
+        self.price_volume += entry.0;
+        self.price_squared_volume += entry.1;
+        self.volume += entry.2;
+
+        if let VwapPeriod::Rolling(period) = self.period {
+            if self.window.len() > period {
+                let (pv, psv, v) = self.window.pop_front().unwrap_or_default();
+                self.price_volume -= pv;
+                self.price_squared_volume -= psv;
+                self.volume -= v;
+            }
+
+            if self.window.len() < period {
+                return None;
+            }
+        }
+
+        if self.volume <= 0.0 {
+            return self.current;
+        }
+
+        let vwap = self.price_volume / self.volume;
+        let std_dev = (self.price_squared_volume / self.volume - vwap * vwap)
+            .max(0.0)
+            .sqrt();
+
+        self.current = Some(VwapValue {
+            vwap,
+            upper: vwap + self.multiplier * std_dev,
+            lower: vwap - self.multiplier * std_dev,
+        });
+        self.current
+    }
+
+    fn get_value(&self) -> Option<VwapValue> {
+        self.current
+    }
+
+    fn reset(&mut self) {
+        self.clear_sums();
+        self.session = None;
+        self.current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::{assert_close, ohlcv};
+
+    // Flat candles so the typical price is the price itself.
+    fn candle(start: i64, price: f64, volume: f64) -> Candlestick {
+        Candlestick {
+            start,
+            ..ohlcv(price, price, price, price, volume)
+        }
+    }
+
+    #[test]
+    fn session_weights_by_volume_and_restarts_each_day() {
+        let mut vwap = Vwap::new(VwapPeriod::Session, 1.0);
+        assert_eq!(vwap.on_candle(&candle(0, 10.0, 0.0)), None);
+
+        vwap.on_candle(&candle(60, 10.0, 1.0));
+        // (10 + 60) / 4, variance 1300 / 4 - 17.5^2.
+        let value = vwap.on_candle(&candle(120, 20.0, 3.0)).unwrap();
+        assert_close(value.vwap, 17.5);
+        assert_close(value.upper, 17.5 + 18.75_f64.sqrt());
+        assert_close(value.lower, 17.5 - 18.75_f64.sqrt());
+
+        let value = vwap.on_candle(&candle(SECONDS_PER_DAY, 30.0, 2.0)).unwrap();
+        assert_close(value.vwap, 30.0);
+        assert_close(value.upper, 30.0);
+    }
+
+    #[test]
+    fn rolling_drops_the_oldest_candle() {
+        let mut vwap = Vwap::new(VwapPeriod::Rolling(2), 1.0);
+
+        assert_eq!(vwap.on_candle(&candle(0, 10.0, 1.0)), None);
+        assert_close(vwap.on_candle(&candle(60, 20.0, 3.0)).unwrap().vwap, 17.5);
+        assert_close(vwap.on_candle(&candle(120, 30.0, 1.0)).unwrap().vwap, 22.5);
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+
+use crate::model::event::Candlestick;
+
+use super::Indicator;
+
+// Linearly weighted average, the newest price has weight `period`.
+#[derive(Debug)]
+pub struct Wma {
+    period: usize,
+    values: VecDeque<f64>,
+    sum: f64,
+    weighted_sum: f64,
+}
+
+impl Wma {
+    pub fn new(period: usize) -> Self {
+        Wma {
+            period,
+            values: VecDeque::with_capacity(period),
+            sum: 0.0,
+            weighted_sum: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, price: f64) -> Option<f64> {
+        if self.values.len() == self.period {
+            // Every weight drops by one and the oldest price falls out.
+            self.weighted_sum += self.period as f64 * price - self.sum;
+            self.sum -= self.values.pop_front().unwrap_or_default();
+        } else {
+            self.weighted_sum += (self.values.len() + 1) as f64 * price;
+        }
+
+        self.values.push_back(price);
+        self.sum += price;
+
+        self.get_value()
+    }
+}
+
+impl Indicator for Wma {
+    type Output = f64;
+
+    fn warmup_period(&self) -> usize {
+        self.period
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) -> Option<f64> {
+        self.update(candle.close)
+    }
+
+    fn get_value(&self) -> Option<f64> {
+        if self.values.len() == self.period {
+            let weights = (self.period * (self.period + 1) / 2) as f64;
+            Some(self.weighted_sum / weights)
+        } else {
+            None
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Wma::new(self.period);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::assert_close;
+
+    #[test]
+    fn newest_price_has_the_largest_weight() {
+        let mut wma = Wma::new(3);
+
+        assert_eq!(wma.update(1.0), None);
+        assert_eq!(wma.update(2.0), None);
+        // (1 * 1 + 2 * 2 + 3 * 3) / 6
+        assert_close(wma.update(3.0).unwrap(), 14.0 / 6.0);
+        // (2 * 1 + 3 * 2 + 4 * 3) / 6
+        assert_close(wma.update(4.0).unwrap(), 20.0 / 6.0);
+        // (3 * 1 + 4 * 2 + 10 * 3) / 6
+        assert_close(wma.update(10.0).unwrap(), 41.0 / 6.0);
+    }
+
+    #[test]
+    fn rolling_sums_match_a_full_recompute() {
+        let mut wma = Wma::new(4);
+        let prices: Vec<f64> = (0..30).map(|i| ((i * 17) % 11) as f64).collect();
+
+        for (i, &price) in prices.iter().enumerate() {
+            let value = wma.update(price);
+
+            if i >= 3 {
+                let expected = prices[i - 3..=i]
+                    .iter()
+                    .zip(1..)
+                    .map(|(price, weight)| price * weight as f64)
+                    .sum::<f64>()
+                    / 10.0;
+                assert_close(value.unwrap(), expected);
+            }
+        }
+    }
+}
@@ -1,34 +1,87 @@
 use std::{
+    collections::HashMap,
     sync::{
         atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
         Arc,
     },
     thread,
+    time::Instant,
 };
 
-use account::{get_product_candle, BotAccount, WS_URL};
+use account::{get_product_candle, BotAccount, PositionSide, WS_URL};
+use arbitrage::{ArbitrageConfig, ArbitrageMonitor};
 use coin::CoinSymbol;
+use config::{BotConfig, SymbolConfig, TradeMode};
+use connection::{HeartbeatWatchdog, SequenceTracker};
+use dca::Dca;
+use grid::Grid;
+use market_maker::MarketMaker;
 use model::{
-    event::{CandleEvent, CandleHistory, EventType},
+    channel::CandleChannelMessage,
+    event::{CandleEvent, CandleHistory, Candlestick, EventType},
     TradeSide,
 };
+use order_book::{BookError, OrderBook};
+use pairs::{PairConfig, PairTrader};
 use smallvec::SmallVec;
 
 use trading_bot::{IndicatorResult, TradeSignal, TradingBot};
 use tungstenite::{connect, Message};
-use util::{market_subcribe_string, subscribe};
+use util::{market_subcribe_string, set_read_timeout, subscribe, subscribe_channels, WsStream};
 
-use crate::model::event::Event;
+use crate::model::event::{Event, EventMessage, MessageError};
 
 mod account;
+mod arbitrage;
 mod coin;
+mod config;
+mod connection;
+mod dca;
+mod grid;
 mod indicators;
+mod market_maker;
 mod model;
+mod order_book;
+mod order_manager;
+mod pairs;
+mod strategy;
 mod trading_bot;
+mod transform;
 mod util;
 
+const READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Debug, Clone, Copy)]
+struct ShutdownOptions {
+    flatten_positions: bool,
+    cancel_orders: bool,
+}
+
+impl ShutdownOptions {
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+
+        ShutdownOptions {
+            flatten_positions: args.iter().any(|arg| arg == "--flatten-on-exit"),
+            cancel_orders: args.iter().any(|arg| arg == "--cancel-on-exit"),
+        }
+    }
+}
+
 fn main() {
     let keep_running = Arc::new(AtomicBool::new(true));
+    let shutdown_options = ShutdownOptions::from_args();
+    let config = BotConfig::load();
+
+    // Threads notice the flag within READ_TIMEOUT and shut down in turn.
+    let handler_keep_running = keep_running.clone();
+    ctrlc::set_handler(move || {
+        println!("Shutdown requested, finishing up...");
+        handler_keep_running.store(false, Ordering::Relaxed);
+    })
+    .expect("Failed to set shutdown handler");
 
     let symbols = [
         CoinSymbol::Xrp,
@@ -41,22 +94,94 @@ fn main() {
 
     let num_symbols = symbols.len();
 
+    // Pairs are skipped unless both coins have a thread to send their candles.
+    let pairs: Vec<(PairConfig, CoinSymbol, CoinSymbol)> = config
+        .get_pairs()
+        .into_iter()
+        .filter_map(|pair| match pair.symbols() {
+            Some((a, b)) if symbols.contains(&a) && symbols.contains(&b) => Some((pair, a, b)),
+            _ => {
+                println!(
+                    "Skipping pair {}/{}, both coins must be traded",
+                    pair.a, pair.b
+                );
+                None
+            }
+        })
+        .collect();
+
+    if let Some(arbitrage_config) = config.get_arbitrage() {
+        let arbitrage_symbols = arbitrage_config.get_symbols(&symbols);
+        let arbitrage_keep_running = keep_running.clone();
+        let handle = thread::spawn(move || {
+            arbitrage_task(
+                arbitrage_keep_running,
+                num_symbols,
+                arbitrage_config,
+                arbitrage_symbols,
+            )
+        });
+        handles.push(handle);
+    }
+
+    let mut pair_sender = None;
+    if !pairs.is_empty() {
+        let (sender, receiver) = mpsc::channel();
+        let pair_keep_running = keep_running.clone();
+        let handle = thread::spawn(move || {
+            pair_trading_task(pair_keep_running, num_symbols, pairs, receiver)
+        });
+        handles.push(handle);
+        pair_sender = Some(sender);
+    }
+
     for symbol in symbols.into_iter() {
         let coin_keep_running = keep_running.clone();
-        let handle =
-            thread::spawn(move || coin_trading_task(coin_keep_running, symbol, num_symbols));
+        let symbol_config = config.get_symbol_config(symbol);
+        let coin_pair_sender = pair_sender.clone();
+        let handle = thread::spawn(move || {
+            coin_trading_task(
+                coin_keep_running,
+                symbol,
+                num_symbols,
+                shutdown_options,
+                symbol_config,
+                coin_pair_sender,
+            )
+        });
         handles.push(handle);
     }
+    drop(pair_sender);
 
     for handle in handles {
         handle.join().unwrap();
     }
 }
 
-fn coin_trading_task(keep_running: Arc<AtomicBool>, symbol: CoinSymbol, num_symbols: usize) {
-    let mut trading_bot = TradingBot::new();
-    let mut account_bot = BotAccount::new(num_symbols);
+fn coin_trading_task(
+    keep_running: Arc<AtomicBool>,
+    symbol: CoinSymbol,
+    num_symbols: usize,
+    shutdown_options: ShutdownOptions,
+    symbol_config: SymbolConfig,
+    pair_sender: Option<Sender<CandleChannelMessage>>,
+) {
+    let mut trading_bot = TradingBot::new(&symbol_config);
+    let mut account_bot = BotAccount::new(num_symbols, symbol_config.mode.clone());
     account_bot.update_balances(symbol);
+    account_bot.load_state(symbol);
+
+    let mut grid = symbol_config.grid.clone().map(|config| {
+        let product = account_bot.get_product(symbol);
+        Grid::new(symbol, config, product, account_bot.get_maker_fee())
+    });
+
+    let mut market_maker = symbol_config.market_maker.clone().map(|config| {
+        let product = account_bot.get_product(symbol);
+        MarketMaker::new(symbol, config, product, account_bot.get_maker_fee())
+    });
+
+    let mut dca = symbol_config.dca.clone().map(Dca::new);
 
     let (mut socket, _) = connect(WS_URL).expect("Failed to connect to socket");
 
@@ -65,25 +190,140 @@ fn coin_trading_task(keep_running: Arc<AtomicBool>, symbol: CoinSymbol, num_symb
     let market_string =
         market_subcribe_string(&String::from(symbol), &String::from(CoinSymbol::Usdc));
 
+    let mut order_book = OrderBook::new(&market_string);
+    let mut sequence_tracker = SequenceTracker::new();
+    let mut heartbeat_watchdog = HeartbeatWatchdog::new(Instant::now());
+
+    set_read_timeout(&socket, READ_TIMEOUT);
     subscribe(&mut socket, &market_string, "subscribe");
 
     let mut backoff_time = 1;
 
     while keep_running.load(Ordering::Relaxed) {
+        let mut reconnect = false;
+
         match socket.read_message() {
             Ok(message) => match message {
                 Message::Text(msg) => {
                     backoff_time = 1;
-                    let event: Event = serde_json::from_str(&msg).unwrap();
+                    let parsed = EventMessage::parse(&msg);
 
-                    match event {
+                    // A message that fails to parse still used up its sequence
+                    // number, so it isn't mistaken for a gap on the next one.
+                    let sequence_num = match &parsed {
+                        Ok(message) => Some(message.sequence_num),
+                        Err(_) => EventMessage::peek_sequence_num(&msg),
+                    };
+                    let gap = sequence_num.and_then(|num| sequence_tracker.check(num).err());
+
+                    if let Some(gap) = gap {
+                        let found_on = match &parsed {
+                            Ok(message) => {
+                                format!("{} {} channel", message.timestamp, message.event.channel())
+                            }
+                            Err(_) => "an unparsed message".to_string(),
+                        };
+                        println!(
+                            "{}: {} on {}, total gaps: {}. Resubscribing for snapshot",
+                            market_string,
+                            gap,
+                            found_on,
+                            sequence_tracker.get_gap_count()
+                        );
+                        resync(
+                            &mut socket,
+                            &market_string,
+                            &mut order_book,
+                            &mut heartbeat_watchdog,
+                        );
+                        // The message itself is still good, only a level2
+                        // update is refused until the new snapshot comes in.
+                    }
+
+                    let message = match parsed {
+                        Ok(message) => message,
+                        Err(e) => {
+                            handle_message_error(&market_string, &msg, e);
+                            continue;
+                        }
+                    };
+
+                    match message.event {
                         Event::Subscriptions(_) => (),
-                        Event::Heartbeats(_) => (),
+                        Event::Heartbeats(heartbeats) => {
+                            for heartbeat in heartbeats.iter() {
+                                if let Err(e) = heartbeat_watchdog.record(heartbeat, Instant::now())
+                                {
+                                    println!("{}: {}", market_string, e);
+                                    reconnect = true;
+                                }
+                            }
+                        }
                         Event::Candle(candles) => {
-                            let indicator_result = handle_candle(candles, &mut trading_bot, symbol);
+                            let indicator_result =
+                                handle_candle(candles, &mut trading_bot, &mut dca, symbol);
                             if let Some(res) = indicator_result {
+                                if let Some(sender) = pair_sender.as_ref() {
+                                    // The pairs thread only stops on shutdown.
+                                    let _ = sender.send(CandleChannelMessage {
+                                        symbol,
+                                        candle: res.candle,
+                                    });
+                                }
+                                if let Some(dca) = dca.as_mut() {
+                                    dca.on_schedule(symbol, &mut account_bot);
+                                }
+                                if order_book.is_synced() {
+                                    println!("{}", order_book);
+                                }
+                                println!("{}", heartbeat_watchdog);
                                 println!("CAN TRADE: {}", trading_bot.get_can_trade());
-                                handle_signal(symbol, res, &mut account_bot, &mut trading_bot);
+                                if grid.is_none() && market_maker.is_none() {
+                                    handle_signal(symbol, res, &mut account_bot, &mut trading_bot);
+                                }
+                            }
+                        }
+                        Event::Ticker(ticker_events) => {
+                            for ticker in ticker_events.iter().flat_map(|e| e.tickers.iter()) {
+                                if ticker.product_id != market_string {
+                                    continue;
+                                }
+                                match grid.as_mut() {
+                                    Some(grid) => grid.on_price(ticker.price, &mut account_bot),
+                                    None => handle_ticker(symbol, ticker.price, &mut account_bot),
+                                }
+                            }
+                        }
+                        Event::Unknown(channel) => {
+                            println!("{}: Unhandled {} message: {}", market_string, channel, msg)
+                        }
+                        Event::Level2(book_events) => {
+                            for book_event in book_events.iter() {
+                                match order_book.apply_event(book_event) {
+                                    Ok(()) => (),
+                                    // Updates were missed, only a new snapshot fixes it.
+                                    Err(e @ BookError::Crossed { .. }) => {
+                                        println!(
+                                            "Order book {}: {}. Resubscribing for snapshot",
+                                            market_string, e
+                                        );
+                                        resync(
+                                            &mut socket,
+                                            &market_string,
+                                            &mut order_book,
+                                            &mut heartbeat_watchdog,
+                                        );
+                                        break;
+                                    }
+                                    Err(e) => println!("Order book {}: {}", market_string, e),
+                                }
+                            }
+                            if let Some(market_maker) = market_maker.as_mut() {
+                                market_maker.on_book(
+                                    &order_book,
+                                    trading_bot.get_raw_atr_value(),
+                                    &mut account_bot,
+                                );
                             }
                         }
                     }
@@ -92,68 +332,336 @@ fn coin_trading_task(keep_running: Arc<AtomicBool>, symbol: CoinSymbol, num_symb
                 Message::Binary(_) | Message::Pong(_) => (),
                 Message::Close(e) => println!("Websocket closed: {:?}", e),
             },
-            Err(_) => {
-                println!(
-                    "Connection lost. Reconnecting in {} seconds...",
-                    backoff_time
-                );
-                std::thread::sleep(std::time::Duration::from_secs(backoff_time));
-
-                backoff_time = (backoff_time * 2).min(60); // Double the backoff time, but cap it at 60 seconds
-                let connection_result = connect(WS_URL);
-                if let Ok((new_socket, _)) = connection_result {
-                    socket = new_socket;
-                    println!("Successfully reconnected!");
-
-                    // Re-subscribe after reconnecting
-                    let market_string = market_subcribe_string(
-                        &String::from(symbol),
-                        &String::from(CoinSymbol::Usdc),
-                    );
-                    subscribe(&mut socket, &market_string, "subscribe");
-                } else {
-                    println!("Failed to reconnect. Will try again...");
+            // Read timed out, the watchdog below decides if the connection is dead.
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) => {}
+            Err(_) => reconnect = true,
+        }
+
+        if let Err(e) = heartbeat_watchdog.check(Instant::now()) {
+            println!("{}: {}", market_string, e);
+            reconnect = true;
+        }
+
+        if reconnect {
+            println!(
+                "Connection lost. Reconnecting in {} seconds...",
+                backoff_time
+            );
+            std::thread::sleep(std::time::Duration::from_secs(backoff_time));
+
+            backoff_time = (backoff_time * 2).min(60); // Double the backoff time, but cap it at 60 seconds
+            let connection_result = connect(WS_URL);
+            if let Ok((new_socket, _)) = connection_result {
+                socket = new_socket;
+                println!("Successfully reconnected!");
+
+                // The book is rebuilt and verified from the snapshot sent on subscribe.
+                order_book.reset();
+                sequence_tracker.reset();
+                heartbeat_watchdog.reset(Instant::now());
+                set_read_timeout(&socket, READ_TIMEOUT);
+
+                // Candles were missed while disconnected, so the indicators
+                // are rebuilt from history when the snapshot comes in.
+                trading_bot.reset();
+                if let Some(dca) = dca.as_mut() {
+                    dca.reset();
                 }
+
+                // Re-subscribe after reconnecting
+                let market_string =
+                    market_subcribe_string(&String::from(symbol), &String::from(CoinSymbol::Usdc));
+                subscribe(&mut socket, &market_string, "subscribe");
+            } else {
+                println!("Failed to reconnect. Will try again...");
             }
         }
     }
+
+    if let Some(grid) = grid.as_mut() {
+        grid.stop(&mut account_bot);
+    }
+    if let Some(market_maker) = market_maker.as_mut() {
+        market_maker.stop(&mut account_bot);
+    }
+
+    shutdown(
+        symbol,
+        &mut socket,
+        &market_string,
+        &mut account_bot,
+        shutdown_options,
+        trading_bot.candle.high,
+    );
+}
+
+// Trades the configured pairs on closed candles sent over from the coin threads.
+fn pair_trading_task(
+    keep_running: Arc<AtomicBool>,
+    num_symbols: usize,
+    pairs: Vec<(PairConfig, CoinSymbol, CoinSymbol)>,
+    receiver: Receiver<CandleChannelMessage>,
+) {
+    let mut account_bot = BotAccount::new(num_symbols, TradeMode::LongOnly);
+    account_bot.update_balances(CoinSymbol::Usdc);
+
+    let mut traders: Vec<PairTrader> = pairs
+        .into_iter()
+        .map(|(config, a, b)| PairTrader::new(config, a, b, &account_bot))
+        .collect();
+
+    let mut recorded = HashMap::new();
+    pairs::record_holdings(&traders, &mut recorded);
+
+    while keep_running.load(Ordering::Relaxed) {
+        match receiver.recv_timeout(READ_TIMEOUT) {
+            Ok(message) => {
+                for trader in traders.iter_mut().filter(|t| t.uses(message.symbol)) {
+                    trader.on_candle(message.symbol, message.candle, &mut account_bot);
+                }
+                pairs::record_holdings(&traders, &mut recorded);
+            }
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    println!("Pairs: Shutting down");
+    account_bot.wait_for_orders();
+    for trader in traders.iter() {
+        trader.save_state();
+    }
+}
+
+// Streams the USD and USDC tickers of each coin on a connection of its own.
+fn arbitrage_task(
+    keep_running: Arc<AtomicBool>,
+    num_symbols: usize,
+    config: ArbitrageConfig,
+    symbols: Vec<CoinSymbol>,
+) {
+    const CHANNELS: [&str; 2] = ["heartbeats", "ticker"];
+
+    let mut account_bot = BotAccount::new(num_symbols, TradeMode::LongOnly);
+    account_bot.update_balances(CoinSymbol::Usdc);
+
+    let mut monitors: Vec<ArbitrageMonitor> = symbols
+        .into_iter()
+        .map(|symbol| ArbitrageMonitor::new(symbol, config.clone(), &account_bot))
+        .collect();
+    let markets: Vec<String> = monitors
+        .iter()
+        .flat_map(|monitor| monitor.get_product_ids())
+        .collect();
+
+    let (mut socket, _) = connect(WS_URL).expect("Failed to connect to socket");
+    let mut heartbeat_watchdog = HeartbeatWatchdog::new(Instant::now());
+    set_read_timeout(&socket, READ_TIMEOUT);
+    subscribe_channels(&mut socket, &markets, &CHANNELS, "subscribe");
+
+    let mut backoff_time = 1;
+
+    while keep_running.load(Ordering::Relaxed) {
+        let mut reconnect = false;
+
+        match socket.read_message() {
+            Ok(Message::Text(msg)) => {
+                backoff_time = 1;
+                match EventMessage::parse(&msg) {
+                    Ok(message) => match message.event {
+                        Event::Ticker(ticker_events) => {
+                            for ticker in ticker_events.iter().flat_map(|e| e.tickers.iter()) {
+                                for monitor in monitors.iter_mut() {
+                                    monitor.on_ticker(ticker, &mut account_bot);
+                                }
+                            }
+                        }
+                        Event::Heartbeats(heartbeats) => {
+                            for heartbeat in heartbeats.iter() {
+                                if let Err(e) = heartbeat_watchdog.record(heartbeat, Instant::now())
+                                {
+                                    println!("Arbitrage: {}", e);
+                                    reconnect = true;
+                                }
+                            }
+                        }
+                        _ => (),
+                    },
+                    Err(e) => handle_message_error("Arbitrage", &msg, e),
+                }
+            }
+            Ok(Message::Ping(_)) => socket.write_message(Message::Pong(vec![])).unwrap(),
+            Ok(Message::Close(e)) => println!("Arbitrage websocket closed: {:?}", e),
+            Ok(_) => (),
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) => {}
+            Err(_) => reconnect = true,
+        }
+
+        if let Err(e) = heartbeat_watchdog.check(Instant::now()) {
+            println!("Arbitrage: {}", e);
+            reconnect = true;
+        }
+
+        if reconnect {
+            println!(
+                "Arbitrage connection lost. Reconnecting in {} seconds...",
+                backoff_time
+            );
+            std::thread::sleep(std::time::Duration::from_secs(backoff_time));
+            backoff_time = (backoff_time * 2).min(60);
+
+            if let Ok((new_socket, _)) = connect(WS_URL) {
+                socket = new_socket;
+                heartbeat_watchdog.reset(Instant::now());
+                set_read_timeout(&socket, READ_TIMEOUT);
+                subscribe_channels(&mut socket, &markets, &CHANNELS, "subscribe");
+            }
+        }
+    }
+
+    println!("Arbitrage: Shutting down");
+    subscribe_channels(&mut socket, &markets, &CHANNELS, "unsubscribe");
+    if let Err(e) = socket.close(None) {
+        println!("Arbitrage: Failed to close socket: {}", e);
+    }
+    account_bot.wait_for_orders();
+}
+
+fn shutdown(
+    symbol: CoinSymbol,
+    socket: &mut WsStream,
+    market_string: &str,
+    account_bot: &mut BotAccount,
+    options: ShutdownOptions,
+    high: f64,
+) {
+    println!("{}: Shutting down", market_string);
+
+    subscribe(socket, market_string, "unsubscribe");
+    if let Err(e) = socket.close(None) {
+        println!("{}: Failed to close socket: {}", market_string, e);
+    }
+
+    account_bot.wait_for_orders();
+
+    if options.cancel_orders {
+        let order_ids: Vec<String> = account_bot
+            .get_open_orders(symbol)
+            .into_iter()
+            .map(|order| order.order_id)
+            .collect();
+        println!(
+            "{}: Cancelling {} open orders",
+            market_string,
+            order_ids.len()
+        );
+        account_bot.cancel_orders(&order_ids);
+    }
+
+    if options.flatten_positions && !account_bot.can_trade() {
+        println!("{}: Closing open position before exit", market_string);
+        let atr = account_bot.get_last_atr();
+        account_bot.close_position(symbol, atr, high);
+        account_bot.wait_for_orders();
+    }
+
+    account_bot.save_state(symbol);
+}
+
+// Drops the book and resubscribes so coinbase sends a fresh snapshot.
+fn resync(
+    socket: &mut WsStream,
+    market: &str,
+    order_book: &mut OrderBook,
+    heartbeat_watchdog: &mut HeartbeatWatchdog,
+) {
+    order_book.reset();
+    heartbeat_watchdog.reset(Instant::now());
+    subscribe(socket, market, "unsubscribe");
+    subscribe(socket, market, "subscribe");
+}
+
+fn handle_message_error(market: &str, msg: &str, error: MessageError) {
+    println!("{}: {}", market, error);
+
+    match error {
+        MessageError::Coinbase(e) if e.is_auth_failure() => println!(
+            "{}: Check API_KEY and API_SECRET are valid and the system clock is in sync",
+            market
+        ),
+        MessageError::Coinbase(_) => (),
+        MessageError::Malformed(_) => println!("{}: Raw message: {}", market, msg),
+    }
 }
 
 fn handle_candle(
     candles: SmallVec<[CandleEvent; 1]>,
     trading_bot: &mut TradingBot,
+    dca: &mut Option<Dca>,
     symbol: CoinSymbol,
 ) -> Option<IndicatorResult> {
     for candle_event in candles.iter() {
         if candle_event.event_type == EventType::Snapshot && !trading_bot.initialise {
-            let hist_candles =
-                get_history_candles(symbol, candle_event.candles.last().unwrap().start);
+            let hist_candles = get_history_candles(
+                symbol,
+                candle_event.candles.last().unwrap().start,
+                trading_bot.get_warmup_period(),
+            );
 
             trading_bot.initialise = true;
 
             for hist_candle in hist_candles.candles.iter().rev() {
                 trading_bot.one_minute_update(*hist_candle);
+                if let Some(dca) = dca.as_mut() {
+                    dca.on_candle(hist_candle);
+                }
             }
 
             for snap_candle in candle_event.candles.iter() {
                 trading_bot.one_minute_update(*snap_candle);
+                if let Some(dca) = dca.as_mut() {
+                    dca.on_candle(snap_candle);
+                }
                 trading_bot.candle = *snap_candle;
             }
+
+            if trading_bot.uses_pivots() {
+                match get_previous_day_candle(symbol, trading_bot.candle.start) {
+                    Some(day) => trading_bot.set_previous_day(&day),
+                    None => println!("{:?}: No daily candle for pivot points", symbol),
+                }
+            }
             return None;
         }
         if candle_event.event_type == EventType::Update {
             for candle in candle_event.candles.iter() {
                 if candle.start != trading_bot.candle.start {
                     println!("{:?}", trading_bot.candle);
-                    trading_bot.one_minute_update(trading_bot.candle);
-                    let signal = trading_bot.get_signal(trading_bot.candle.close);
+                    let closed = trading_bot.candle;
+                    trading_bot.one_minute_update(closed);
+                    if let Some(dca) = dca.as_mut() {
+                        dca.on_candle(&trading_bot.candle);
+                    }
+                    let signal = trading_bot.get_signal();
                     let atr = trading_bot.get_atr_value();
+                    let stop = trading_bot.get_stop(&trading_bot.candle);
                     trading_bot.candle = *candle;
 
                     return Some(IndicatorResult {
+                        candle: closed,
                         signal,
                         atr,
                         high: candle.high,
+                        low: candle.low,
+                        stop,
                     });
                 } else {
                     trading_bot.candle = *candle;
@@ -165,11 +673,35 @@ fn handle_candle(
     None
 }
 
-fn get_history_candles(symbol: CoinSymbol, recent_start: i64) -> CandleHistory {
+// Stops are checked on every ticker so a fall within a candle is acted on
+// straight away, entries are still left to the candle close.
+fn handle_ticker(symbol: CoinSymbol, price: f64, bot_account: &mut BotAccount) {
+    if bot_account.update_live_price(price) {
+        println!("Closing Open Position On Ticker");
+        let atr = bot_account.get_last_atr();
+        bot_account.close_position(symbol, atr, price);
+        bot_account.update_balances(symbol);
+    }
+}
+
+// Coinbase returns at most 300 candles per request.
+fn get_history_candles(symbol: CoinSymbol, recent_start: i64, warmup: usize) -> CandleHistory {
+    let candle_count = warmup.clamp(100, 300) as i64;
     let end = recent_start - 300;
-    let start = end - 30000;
+    let start = end - candle_count * 300;
 
-    get_product_candle(symbol, start, end)
+    get_product_candle(symbol, start, end, "FIVE_MINUTE")
+}
+
+// Daily candle for the UTC day before the one `recent_start` falls in.
+fn get_previous_day_candle(symbol: CoinSymbol, recent_start: i64) -> Option<Candlestick> {
+    let day_start = recent_start - recent_start.rem_euclid(SECONDS_PER_DAY);
+    let start = day_start - SECONDS_PER_DAY;
+
+    get_product_candle(symbol, start, day_start - 1, "ONE_DAY")
+        .candles
+        .into_iter()
+        .find(|candle| candle.start == start)
 }
 
 fn handle_signal(
@@ -181,13 +713,19 @@ fn handle_signal(
     println!("Current Signal: {:?}", indicator_result.signal);
 
     if !bot_account.can_trade() {
-        let should_sell =
-            bot_account.update_coin_position(indicator_result.high, indicator_result.atr.unwrap());
+        let should_close = bot_account.update_coin_position(
+            indicator_result.high,
+            indicator_result.low,
+            indicator_result.atr.unwrap(),
+        );
 
-        if should_sell {
+        // A buy signal ends a short or defensive position so the long can be entered.
+        let flip_to_long = indicator_result.signal == TradeSignal::Buy
+            && bot_account.get_position() != PositionSide::Long;
+
+        if should_close || flip_to_long {
             println!("Closing Open Position");
-            bot_account.create_order(
-                TradeSide::Sell,
+            bot_account.close_position(
                 symbol,
                 indicator_result.atr.unwrap(),
                 indicator_result.high,
@@ -207,6 +745,27 @@ fn handle_signal(
             indicator_result.high,
         );
         trading_bot.set_can_trade(false);
+
+        if let Some(stop) = indicator_result.stop {
+            if !bot_account.can_trade() {
+                bot_account.set_stop_loss(stop);
+            }
+        }
+        bot_account.update_balances(symbol);
+    }
+    if bot_account.can_trade()
+        && bot_account.trades_sell_side()
+        && indicator_result.signal == TradeSignal::Sell
+        && trading_bot.get_can_short()
+    {
+        println!("Entering Sell Side Position");
+        bot_account.open_sell_side(
+            symbol,
+            indicator_result.atr.unwrap(),
+            indicator_result.low,
+            indicator_result.low,
+        );
+        trading_bot.set_can_short(false);
         bot_account.update_balances(symbol);
     }
 }
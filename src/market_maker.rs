@@ -0,0 +1,378 @@
+use std::time::{Duration, Instant};
+
+use rust_decimal::prelude::ToPrimitive;
+use serde::Deserialize;
+
+use crate::{
+    account::BotAccount,
+    coin::CoinSymbol,
+    model::{account::Product, TradeSide},
+    order_book::OrderBook,
+    order_manager::{LimitOrder, OrderManager, Settlement},
+};
+
+// Fills are polled over rest so keep it well inside the rate limits.
+const MARKET_MAKER_UPDATE_INTERVAL: Duration = Duration::from_secs(5);
+// Quotes are refreshed at least this often even if the mid holds still.
+const MARKET_MAKER_REQUOTE_INTERVAL: Duration = Duration::from_secs(60);
+
+const BID_TAG: usize = 0;
+const ASK_TAG: usize = 1;
+
+const DEFAULT_SPREAD_BPS: f64 = 10.0;
+const DEFAULT_MM_ATR_MULTIPLIER: f64 = 0.5;
+const DEFAULT_SKEW: f64 = 1.0;
+const DEFAULT_CANCEL_ATR: f64 = 0.5;
+
+fn default_spread_bps() -> f64 {
+    DEFAULT_SPREAD_BPS
+}
+
+fn default_atr_multiplier() -> f64 {
+    DEFAULT_MM_ATR_MULTIPLIER
+}
+
+fn default_skew() -> f64 {
+    DEFAULT_SKEW
+}
+
+fn default_cancel_atr() -> f64 {
+    DEFAULT_CANCEL_ATR
+}
+
+// Quotes order_size USDC a side, spread_bps plus atr_multiplier ATRs either
+// side of the mid. max_inventory is the USDC value of coin it will hold.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MarketMakerConfig {
+    pub order_size: f64,
+    pub max_inventory: f64,
+    #[serde(default = "default_spread_bps")]
+    pub spread_bps: f64,
+    #[serde(default = "default_atr_multiplier")]
+    pub atr_multiplier: f64,
+    // Fraction of the half spread the quotes move down at max inventory.
+    #[serde(default = "default_skew")]
+    pub skew: f64,
+    // Quotes are pulled once the mid moves this many ATRs from where they
+    // were placed.
+    #[serde(default = "default_cancel_atr")]
+    pub cancel_atr: f64,
+}
+
+impl MarketMakerConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.order_size <= 0.0 {
+            return Err(format!(
+                "market maker order_size must be positive, got {}",
+                self.order_size
+            ));
+        }
+
+        if self.max_inventory <= 0.0 {
+            return Err(format!(
+                "market maker max_inventory must be positive, got {}",
+                self.max_inventory
+            ));
+        }
+
+        if self.spread_bps <= 0.0 {
+            return Err(format!(
+                "market maker spread_bps must be positive, got {}",
+                self.spread_bps
+            ));
+        }
+        Ok(())
+    }
+
+    // Distance of each quote from the reservation price. The atr is the raw
+    // candle ATR, atr_multiplier is the only scaling applied to it.
+    fn half_spread(&self, mid: f64, atr: f64) -> f64 {
+        mid * self.spread_bps / 10_000.0 + atr * self.atr_multiplier
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Quotes {
+    mid: f64,
+    time: Instant,
+}
+
+// Spot only, so the ask only sells coin the market maker has bought itself.
+#[derive(Debug)]
+pub struct MarketMaker {
+    symbol: CoinSymbol,
+    config: MarketMakerConfig,
+    orders: OrderManager,
+    maker_fee: f64,
+    inventory: f64,
+    average_cost: f64,
+    pnl: f64,
+    fills: u32,
+    quotes: Option<Quotes>,
+    last_update: Option<Instant>,
+    active: bool,
+}
+
+impl MarketMaker {
+    pub fn new(
+        symbol: CoinSymbol,
+        config: MarketMakerConfig,
+        product: Product,
+        maker_fee: f64,
+    ) -> Self {
+        MarketMaker {
+            symbol,
+            config,
+            orders: OrderManager::new(symbol, product),
+            maker_fee,
+            inventory: 0.0,
+            average_cost: 0.0,
+            pnl: 0.0,
+            fills: 0,
+            quotes: None,
+            last_update: None,
+            active: true,
+        }
+    }
+
+    // Runs on level2 updates, only does anything every
+    // MARKET_MAKER_UPDATE_INTERVAL and once the book and ATR are ready.
+    pub fn on_book(&mut self, book: &OrderBook, atr: Option<f64>, account: &mut BotAccount) {
+        if !self.active
+            || self
+                .last_update
+                .is_some_and(|last| last.elapsed() < MARKET_MAKER_UPDATE_INTERVAL)
+        {
+            return;
+        }
+
+        let (mid, atr) = match (book.verify().ok().and(book.mid_price()), atr) {
+            (Some(mid), Some(atr)) => (mid, atr),
+            _ => return,
+        };
+        self.last_update = Some(Instant::now());
+
+        let mut requote = false;
+        // Cancelled quotes can have filled in part before the cancel landed.
+        for settlement in self.orders.poll_fills(account) {
+            if settlement.filled_size > 0.0 {
+                self.on_fill(settlement);
+                requote = true;
+            }
+        }
+
+        // A move either way is adverse to one side of the quotes.
+        requote |= match self.quotes {
+            Some(quotes) => {
+                (mid - quotes.mid).abs() > self.config.cancel_atr * atr
+                    || quotes.time.elapsed() >= MARKET_MAKER_REQUOTE_INTERVAL
+            }
+            None => true,
+        };
+
+        if requote {
+            self.orders.cancel_all(account);
+            self.quote(mid, atr, account);
+        }
+    }
+
+    fn on_fill(&mut self, fill: Settlement) {
+        let (price, size) = (fill.average_price, fill.filled_size);
+        let fee = price * size * self.maker_fee;
+
+        match fill.order.side {
+            TradeSide::Buy => {
+                let cost = self.average_cost * self.inventory + price * size + fee;
+                self.inventory += size;
+                self.average_cost = cost / self.inventory;
+            }
+            TradeSide::Sell => {
+                let size = size.min(self.inventory);
+                self.pnl += (price - self.average_cost) * size - fee;
+                self.inventory -= size;
+            }
+        }
+        self.fills += 1;
+
+        println!(
+            "{:?} market maker {} filled {} of {} at {}, {:?}, inventory: {}, pnl: {:.4}",
+            self.symbol,
+            fill.order.side,
+            fill.filled_size,
+            fill.order.size,
+            fill.average_price,
+            fill.status,
+            self.inventory,
+            self.pnl
+        );
+    }
+
+    // Shifts both quotes down as inventory builds so sells fill first, and
+    // stops bidding at max_inventory.
+    fn quote(&mut self, mid: f64, atr: f64, account: &mut BotAccount) {
+        let half_spread = self.config.half_spread(mid, atr);
+        let fullness = (self.inventory * mid / self.config.max_inventory).clamp(0.0, 1.0);
+        let reservation = mid - fullness * self.config.skew * half_spread;
+
+        // The reservation price never goes over the mid so the bid can't
+        // cross. A skew over 1 could push the ask through the mid, where a
+        // post only order would be rejected.
+        let bid_price = reservation - half_spread;
+        let ask_price = (reservation + half_spread).max(mid);
+
+        let product = self.orders.get_product();
+        let bid = match (product.round_price(bid_price), fullness < 1.0) {
+            (Some(price), true) => product
+                .round_base(self.config.order_size / bid_price)
+                .map(|size| (price, size)),
+            _ => None,
+        };
+        let ask = product
+            .round_price_up(ask_price)
+            .zip(product.round_base(self.inventory.min(self.config.order_size / ask_price)));
+
+        let min_size = product.base_min_size;
+        let orders = [
+            (TradeSide::Buy, BID_TAG, bid),
+            (TradeSide::Sell, ASK_TAG, ask),
+        ];
+
+        for (side, tag, quote) in orders {
+            let (price, size) = match quote {
+                Some((price, size)) if size.to_f64().unwrap_or(0.0) >= min_size => (price, size),
+                _ => continue,
+            };
+
+            let order = LimitOrder {
+                side,
+                price,
+                size,
+                tag,
+            };
+            self.orders.place_limit(account, order, true);
+        }
+
+        self.quotes = Some(Quotes {
+            mid,
+            time: Instant::now(),
+        });
+    }
+
+    // Cancels the quotes and prints how the session went.
+    pub fn stop(&mut self, account: &mut BotAccount) {
+        if !self.active {
+            return;
+        }
+        self.active = false;
+        self.orders.cancel_all(account);
+
+        println!(
+            "{:?} market maker stopped, fills: {}, inventory: {} at {:.4}, pnl: {:.4}",
+            self.symbol, self.fills, self.inventory, self.average_cost, self.pnl
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use super::*;
+    use crate::model::OrderStatus;
+
+    fn config() -> MarketMakerConfig {
+        MarketMakerConfig {
+            order_size: 10.0,
+            max_inventory: 100.0,
+            spread_bps: DEFAULT_SPREAD_BPS,
+            atr_multiplier: DEFAULT_MM_ATR_MULTIPLIER,
+            skew: DEFAULT_SKEW,
+            cancel_atr: DEFAULT_CANCEL_ATR,
+        }
+    }
+
+    fn market_maker() -> MarketMaker {
+        let product = serde_json::from_value(serde_json::json!({
+            "product_id": "XRP-USDC",
+            "price": "0.5",
+            "quote_min_size": "1",
+            "quote_max_size": "1000000",
+            "base_min_size": "1",
+            "base_max_size": "1000000",
+            "base_increment": "0.1",
+            "quote_increment": "0.01",
+            "price_increment": "0.0001",
+        }))
+        .unwrap();
+
+        MarketMaker::new(CoinSymbol::Xrp, config(), product, 0.0)
+    }
+
+    fn settlement(
+        side: TradeSide,
+        status: OrderStatus,
+        filled_size: f64,
+        price: f64,
+    ) -> Settlement {
+        Settlement {
+            order: LimitOrder {
+                side,
+                price: Decimal::try_from(price).unwrap(),
+                size: Decimal::new(20, 0),
+                tag: BID_TAG,
+            },
+            status,
+            filled_size,
+            average_price: price,
+        }
+    }
+
+    #[test]
+    fn keeps_part_of_a_cancelled_quote() {
+        let mut market_maker = market_maker();
+
+        market_maker.on_fill(settlement(TradeSide::Buy, OrderStatus::Filled, 20.0, 0.5));
+        market_maker.on_fill(settlement(TradeSide::Buy, OrderStatus::Cancelled, 5.0, 0.4));
+        assert_eq!(market_maker.inventory, 25.0);
+        assert!((market_maker.average_cost - 0.48).abs() < 1e-9);
+
+        market_maker.on_fill(settlement(
+            TradeSide::Sell,
+            OrderStatus::Cancelled,
+            10.0,
+            0.6,
+        ));
+        assert_eq!(market_maker.inventory, 15.0);
+        assert!((market_maker.pnl - 1.2).abs() < 1e-9);
+        assert_eq!(market_maker.fills, 3);
+    }
+
+    #[test]
+    fn half_spread_is_bps_of_the_mid_plus_scaled_atr() {
+        // 10 bps of 100 and half of an atr of 2.
+        assert!((config().half_spread(100.0, 2.0) - 1.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn validates_the_config() {
+        assert!(config().validate().is_ok());
+
+        let bad = [
+            MarketMakerConfig {
+                order_size: 0.0,
+                ..config()
+            },
+            MarketMakerConfig {
+                max_inventory: -1.0,
+                ..config()
+            },
+            MarketMakerConfig {
+                spread_bps: 0.0,
+                ..config()
+            },
+        ];
+        for config in bad {
+            assert!(config.validate().is_err(), "{:?}", config);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 use super::string_or_float;
@@ -64,4 +65,43 @@ pub struct Product {
     pub base_min_size: f64,
     #[serde(with = "string_or_float")]
     pub base_max_size: f64,
+    pub base_increment: Decimal,
+    pub quote_increment: Decimal,
+    pub price_increment: Decimal,
+}
+
+impl Product {
+    // Rounds a size down to a valid base_increment.
+    pub fn round_base(&self, size: f64) -> Option<Decimal> {
+        round_down(size, self.base_increment)
+    }
+
+    // Rounds a quote amount down to a valid quote_increment.
+    pub fn round_quote(&self, amount: f64) -> Option<Decimal> {
+        round_down(amount, self.quote_increment)
+    }
+
+    // Rounds a price down to a valid price_increment.
+    pub fn round_price(&self, price: f64) -> Option<Decimal> {
+        round_down(price, self.price_increment)
+    }
+
+    // Rounds a price up to a valid price_increment.
+    pub fn round_price_up(&self, price: f64) -> Option<Decimal> {
+        if self.price_increment.is_zero() {
+            return None;
+        }
+        let price = Decimal::try_from(price).ok()?;
+
+        Some(((price / self.price_increment).ceil() * self.price_increment).normalize())
+    }
+}
+
+fn round_down(value: f64, increment: Decimal) -> Option<Decimal> {
+    if increment.is_zero() {
+        return None;
+    }
+    let value = Decimal::try_from(value).ok()?;
+
+    Some(((value / increment).floor() * increment).normalize())
 }
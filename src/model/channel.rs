@@ -2,7 +2,7 @@ use smallvec::SmallVec;
 
 use crate::{coin::CoinSymbol, trading_bot::TradeSignal};
 
-use super::event::CandleEvent;
+use super::event::{CandleEvent, Candlestick};
 
 #[derive(Debug)]
 pub struct IndicatorChannelMessage {
@@ -10,6 +10,13 @@ pub struct IndicatorChannelMessage {
     pub candles: SmallVec<[CandleEvent; 1]>,
 }
 
+// A closed candle forwarded from a coin thread to the pairs thread.
+#[derive(Debug)]
+pub struct CandleChannelMessage {
+    pub symbol: CoinSymbol,
+    pub candle: Candlestick,
+}
+
 #[derive(Debug)]
 pub struct AccountChannelMessage {
     pub symbol: CoinSymbol,
@@ -1,11 +1,15 @@
 use std::fmt;
 
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use serde::Deserialize;
 use smallvec::SmallVec;
 use uuid::Uuid;
 
-use super::{string_or_float, string_or_i64, OrderStatus, TradeSide};
+use super::{
+    string_or_float, string_or_float_opt, string_or_i64, string_or_u64, OrderStatus, Side,
+    TradeSide,
+};
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum EventType {
@@ -15,6 +19,17 @@ pub enum EventType {
     Update,
 }
 
+// Envelope every websocket message is wrapped in.
+#[derive(Deserialize, Debug)]
+pub struct EventMessage {
+    #[allow(dead_code)]
+    pub client_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub sequence_num: u64,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
 // Event enum
 #[derive(Deserialize, Debug)]
 #[serde(tag = "channel", content = "events")]
@@ -25,6 +40,112 @@ pub enum Event {
     Heartbeats(SmallVec<[HeartbeatEvent; 1]>),
     #[serde(rename = "candles")]
     Candle(SmallVec<[CandleEvent; 1]>),
+    #[serde(rename = "l2_data")]
+    Level2(SmallVec<[Level2Event; 1]>),
+    #[serde(rename = "ticker", alias = "ticker_batch")]
+    Ticker(SmallVec<[TickerEvent; 1]>),
+    // Any channel the bot doesn't handle, such as status or market_trades.
+    #[serde(skip)]
+    Unknown(String),
+}
+
+const KNOWN_CHANNELS: [&str; 6] = [
+    "subscriptions",
+    "heartbeats",
+    "candles",
+    "l2_data",
+    "ticker",
+    "ticker_batch",
+];
+
+// Envelope with the events left unparsed, used when the channel isn't in Event.
+#[derive(Deserialize, Debug)]
+struct RawEventMessage {
+    channel: String,
+    client_id: String,
+    timestamp: DateTime<Utc>,
+    sequence_num: u64,
+}
+
+// Only the sequence number, for messages whose events fail to parse.
+#[derive(Deserialize, Debug)]
+struct SequenceOnly {
+    sequence_num: u64,
+}
+
+impl EventMessage {
+    // Error frames carry no sequence number.
+    pub fn peek_sequence_num(msg: &str) -> Option<u64> {
+        serde_json::from_str::<SequenceOnly>(msg)
+            .ok()
+            .map(|sequence| sequence.sequence_num)
+    }
+
+    pub fn parse(msg: &str) -> Result<Self, MessageError> {
+        let event_error = match serde_json::from_str::<EventMessage>(msg) {
+            Ok(message) => return Ok(message),
+            Err(e) => e,
+        };
+
+        // Unknown channels still carry a sequence number that has to be tracked.
+        if let Ok(raw) = serde_json::from_str::<RawEventMessage>(msg) {
+            if !KNOWN_CHANNELS.contains(&raw.channel.as_str()) {
+                return Ok(EventMessage {
+                    client_id: raw.client_id,
+                    timestamp: raw.timestamp,
+                    sequence_num: raw.sequence_num,
+                    event: Event::Unknown(raw.channel),
+                });
+            }
+        }
+
+        // Errors come without a channel so they only parse as an error message.
+        match serde_json::from_str::<ErrorMessage>(msg) {
+            Ok(error) if error.message_type == "error" => Err(MessageError::Coinbase(error)),
+            _ => Err(MessageError::Malformed(event_error)),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ErrorMessage {
+    #[serde(rename = "type")]
+    pub message_type: String,
+    pub message: String,
+}
+
+impl ErrorMessage {
+    pub fn is_auth_failure(&self) -> bool {
+        self.message.to_lowercase().contains("authentication")
+    }
+}
+
+#[derive(Debug)]
+pub enum MessageError {
+    Coinbase(ErrorMessage),
+    Malformed(serde_json::Error),
+}
+
+impl fmt::Display for MessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageError::Coinbase(error) => write!(f, "Coinbase error: {}", error.message),
+            MessageError::Malformed(e) => write!(f, "Failed to parse message: {}", e),
+        }
+    }
+}
+
+impl Event {
+    pub fn channel(&self) -> &'static str {
+        match self {
+            Event::Subscriptions(_) => "subscriptions",
+            Event::Heartbeats(_) => "heartbeats",
+            Event::Candle(_) => "candles",
+            Event::Level2(_) => "l2_data",
+            Event::Ticker(_) => "ticker",
+            Event::Unknown(_) => "unknown",
+        }
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -42,8 +163,10 @@ pub struct SubscriptionEvent {
 
 #[derive(Debug, Deserialize)]
 pub struct HeartbeatEvent {
-    current_time: String,
-    heartbeat_counter: u64,
+    pub current_time: String,
+    // Sent as a string in the documented examples.
+    #[serde(with = "string_or_u64")]
+    pub heartbeat_counter: u64,
 }
 
 impl fmt::Display for HeartbeatEvent {
@@ -109,3 +232,172 @@ pub struct Candlestick {
 pub struct CandleHistory {
     pub candles: Vec<Candlestick>,
 }
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Level2Event {
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    pub product_id: String,
+    pub updates: Vec<Level2Update>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Level2Update {
+    pub side: Side,
+    pub price_level: Decimal,
+    #[serde(with = "string_or_float")]
+    pub new_quantity: f64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TickerEvent {
+    pub tickers: SmallVec<[Ticker; 1]>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Ticker {
+    pub product_id: String,
+    #[serde(with = "string_or_float")]
+    pub price: f64,
+    #[serde(default, with = "string_or_float_opt")]
+    pub best_bid: Option<f64>,
+    #[serde(default, with = "string_or_float_opt")]
+    pub best_ask: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peeks_the_sequence_of_a_message_that_fails_to_parse() {
+        let msg = r#"{"channel":"l2_data","client_id":"","timestamp":"2023-02-09T20:32:50.714964855Z","sequence_num":42,"events":[{"type":"bad"}]}"#;
+
+        assert!(EventMessage::parse(msg).is_err());
+        assert_eq!(EventMessage::peek_sequence_num(msg), Some(42));
+        assert_eq!(
+            EventMessage::peek_sequence_num(r#"{"type":"error","message":"failure"}"#),
+            None
+        );
+    }
+
+    // Examples from the coinbase advanced trade websocket docs.
+    const HEARTBEATS: &str = r#"{"channel":"heartbeats","client_id":"","timestamp":"2023-06-23T20:31:26.122969572Z","sequence_num":0,"events":[{"current_time":"2023-06-23 20:31:56.121961769 +0000 UTC m=+91717.525857105","heartbeat_counter":"3049"}]}"#;
+    const CANDLES: &str = r#"{"channel":"candles","client_id":"","timestamp":"2023-06-09T20:19:35.39625135Z","sequence_num":1,"events":[{"type":"snapshot","candles":[{"start":"1688998200","high":"1867.72","low":"1865.63","open":"1867.38","close":"1866.81","volume":"0.20269406","product_id":"ETH-USD"}]}]}"#;
+    const LEVEL2: &str = r#"{"channel":"l2_data","client_id":"","timestamp":"2023-02-09T20:32:50.714964855Z","sequence_num":2,"events":[{"type":"snapshot","product_id":"BTC-USD","updates":[{"side":"bid","event_time":"1970-01-01T00:00:00Z","price_level":"21921.73","new_quantity":"0.06317902"},{"side":"offer","event_time":"1970-01-01T00:00:00Z","price_level":"21921.74","new_quantity":"0.02"}]}]}"#;
+    const TICKER: &str = r#"{"channel":"ticker","client_id":"","timestamp":"2023-02-09T20:30:37.167359596Z","sequence_num":3,"events":[{"type":"snapshot","tickers":[{"type":"ticker","product_id":"BTC-USD","price":"21932.98","volume_24_h":"16038.28770938","low_24_h":"21835.29","high_24_h":"23011.18","low_52_w":"15460","high_52_w":"48240","price_percent_chg_24_h":"-4.15775596190603","best_bid":"21931.98","best_bid_quantity":"8000.21","best_ask":"21933.98","best_ask_quantity":"8038.07770144"}]}]}"#;
+    const SUBSCRIPTIONS: &str = r#"{"channel":"subscriptions","client_id":"","timestamp":"2023-02-09T20:32:50.714964855Z","sequence_num":4,"events":[{"subscriptions":{"heartbeats":["heartbeats"],"level2":["BTC-USD"]}}]}"#;
+    const MARKET_TRADES: &str = r#"{"channel":"market_trades","client_id":"","timestamp":"2023-02-09T20:19:35.39625135Z","sequence_num":5,"events":[{"type":"snapshot","trades":[{"trade_id":"000000000","product_id":"ETH-USD","price":"1260.01","size":"0.3","side":"BUY","time":"2019-08-14T20:42:27.265Z"}]}]}"#;
+
+    fn parse(msg: &str) -> Event {
+        EventMessage::parse(msg).unwrap().event
+    }
+
+    #[test]
+    fn parses_heartbeats() {
+        match parse(HEARTBEATS) {
+            Event::Heartbeats(heartbeats) => assert_eq!(heartbeats[0].heartbeat_counter, 3049),
+            event => panic!("expected heartbeats, got {:?}", event),
+        }
+
+        let numeric = HEARTBEATS.replace(r#""3049""#, "3050");
+        match parse(&numeric) {
+            Event::Heartbeats(heartbeats) => assert_eq!(heartbeats[0].heartbeat_counter, 3050),
+            event => panic!("expected heartbeats, got {:?}", event),
+        }
+    }
+
+    #[test]
+    fn parses_candles() {
+        let message = EventMessage::parse(CANDLES).unwrap();
+        assert_eq!(message.sequence_num, 1);
+
+        match message.event {
+            Event::Candle(events) => {
+                assert_eq!(events[0].event_type, EventType::Snapshot);
+                let candle = events[0].candles[0];
+                assert_eq!(candle.start, 1688998200);
+                assert_eq!(
+                    (candle.open, candle.high, candle.low, candle.close),
+                    (1867.38, 1867.72, 1865.63, 1866.81)
+                );
+                assert_eq!(candle.volume, 0.20269406);
+            }
+            event => panic!("expected candles, got {:?}", event),
+        }
+    }
+
+    #[test]
+    fn parses_level2() {
+        match parse(LEVEL2) {
+            Event::Level2(events) => {
+                let event = &events[0];
+                assert_eq!(event.event_type, EventType::Snapshot);
+                assert_eq!(event.product_id, "BTC-USD");
+                assert_eq!(event.updates.len(), 2);
+                assert_eq!(event.updates[0].side, Side::Bid);
+                assert_eq!(event.updates[0].price_level, Decimal::new(2192173, 2));
+                assert_eq!(event.updates[1].side, Side::Offer);
+                assert_eq!(event.updates[1].new_quantity, 0.02);
+            }
+            event => panic!("expected level2, got {:?}", event),
+        }
+    }
+
+    #[test]
+    fn parses_tickers() {
+        match parse(TICKER) {
+            Event::Ticker(events) => {
+                let ticker = &events[0].tickers[0];
+                assert_eq!(ticker.product_id, "BTC-USD");
+                assert_eq!(ticker.price, 21932.98);
+                assert_eq!(ticker.best_bid, Some(21931.98));
+                assert_eq!(ticker.best_ask, Some(21933.98));
+            }
+            event => panic!("expected ticker, got {:?}", event),
+        }
+
+        // ticker_batch has the same events, without the best bid and ask.
+        let batch = TICKER
+            .replace(r#""channel":"ticker""#, r#""channel":"ticker_batch""#)
+            .replace(r#","best_bid":"21931.98""#, "")
+            .replace(r#","best_ask":"21933.98""#, "");
+        match parse(&batch) {
+            Event::Ticker(events) => assert_eq!(events[0].tickers[0].best_bid, None),
+            event => panic!("expected ticker, got {:?}", event),
+        }
+    }
+
+    #[test]
+    fn parses_subscriptions() {
+        assert!(matches!(parse(SUBSCRIPTIONS), Event::Subscriptions(_)));
+    }
+
+    #[test]
+    fn unknown_channel_keeps_its_sequence() {
+        let message = EventMessage::parse(MARKET_TRADES).unwrap();
+
+        assert_eq!(message.sequence_num, 5);
+        assert!(matches!(message.event, Event::Unknown(channel) if channel == "market_trades"));
+    }
+
+    #[test]
+    fn error_frame_is_a_coinbase_error() {
+        let msg = r#"{"type":"error","message":"authentication failure"}"#;
+
+        match EventMessage::parse(msg) {
+            Err(MessageError::Coinbase(error)) => assert!(error.is_auth_failure()),
+            result => panic!("expected a coinbase error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn malformed_json_is_an_error() {
+        for msg in [r#"{"channel":"ticker","#, "", r#"{"type":"status"}"#] {
+            assert!(matches!(
+                EventMessage::parse(msg),
+                Err(MessageError::Malformed(_))
+            ));
+        }
+    }
+}
@@ -24,7 +24,7 @@ impl fmt::Display for TradeSide {
     }
 }
 
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Side {
     Bid,
@@ -40,6 +40,21 @@ pub enum OrderStatus {
     Cancelled,
     Expired,
     Failed,
+    #[serde(other)]
+    Unknown,
+}
+
+impl OrderStatus {
+    // The order will not change any more.
+    pub fn is_settled(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled
+                | OrderStatus::Cancelled
+                | OrderStatus::Expired
+                | OrderStatus::Failed
+        )
+    }
 }
 
 pub(crate) mod string_or_float {
@@ -100,6 +115,27 @@ pub(crate) mod string_or_i64 {
     }
 }
 
+pub(crate) mod string_or_u64 {
+    use serde::{de, Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum StringOrU64 {
+            String(String),
+            Int(u64),
+        }
+
+        match StringOrU64::deserialize(deserializer)? {
+            StringOrU64::String(s) => s.parse().map_err(de::Error::custom),
+            StringOrU64::Int(i) => Ok(i),
+        }
+    }
+}
+
 pub(crate) mod string_or_float_opt {
     use std::fmt;
 
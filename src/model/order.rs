@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::string_or_float; // I assume you're using the 'uuid' crate
+use super::{string_or_float, OrderStatus}; // I assume you're using the 'uuid' crate
 
 #[allow(dead_code)]
 pub enum TradeOrderType {
@@ -99,6 +99,52 @@ pub struct CurrentOrder {
     pub user_id: String,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct OrderSummary {
+    pub order_id: String,
+    pub product_id: String,
+    pub status: OrderStatus,
+    // Can be non zero on a cancelled order that filled in part first.
+    #[serde(default, with = "string_or_float")]
+    pub filled_size: f64,
+    #[serde(default, with = "string_or_float")]
+    pub average_filled_price: f64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SingleOrder {
+    pub order: OrderSummary,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OrderList {
+    pub orders: Vec<OrderSummary>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CancelOrdersResponse {
+    pub results: Vec<CancelOrderResult>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CancelOrderResult {
+    pub success: bool,
+    pub failure_reason: String,
+    pub order_id: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ConvertResponse {
+    pub trade: ConvertTrade,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ConvertTrade {
+    pub id: String,
+    #[serde(default)]
+    pub status: String,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct ApiError {
     pub error: String,
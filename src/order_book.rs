@@ -0,0 +1,344 @@
+use std::{collections::BTreeMap, fmt};
+
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+
+use crate::model::{
+    event::{EventType, Level2Event, Level2Update},
+    Side,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BookError {
+    NotSynced,
+    EmptySide(Side),
+    Crossed { best_bid: f64, best_ask: f64 },
+}
+
+impl fmt::Display for BookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookError::NotSynced => write!(f, "Book has not received a snapshot"),
+            BookError::EmptySide(side) => write!(f, "Book has no {:?} levels", side),
+            BookError::Crossed { best_bid, best_ask } => {
+                write!(f, "Book is crossed, bid: {} ask: {}", best_bid, best_ask)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookDepth {
+    pub bid_size: f64,
+    pub ask_size: f64,
+}
+
+impl BookDepth {
+    /// Returns a value between -1.0 (all asks) and 1.0 (all bids).
+    pub fn imbalance(&self) -> Option<f64> {
+        let total = self.bid_size + self.ask_size;
+
+        if total > 0.0 {
+            Some((self.bid_size - self.ask_size) / total)
+        } else {
+            None
+        }
+    }
+}
+
+/// Local copy of a single product's level2 book, built from the websocket
+/// snapshot and kept up to date with the following updates.
+#[derive(Debug)]
+pub struct OrderBook {
+    product_id: String,
+    bids: BTreeMap<Decimal, f64>,
+    asks: BTreeMap<Decimal, f64>,
+    synced: bool,
+}
+
+impl OrderBook {
+    pub fn new(product_id: &str) -> Self {
+        OrderBook {
+            product_id: product_id.to_string(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            synced: false,
+        }
+    }
+
+    /// Drops every level and waits for a fresh snapshot, used after reconnecting.
+    pub fn reset(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+        self.synced = false;
+    }
+
+    pub fn apply_event(&mut self, event: &Level2Event) -> Result<(), BookError> {
+        if event.product_id != self.product_id {
+            return Ok(());
+        }
+
+        match event.event_type {
+            EventType::Snapshot => {
+                self.reset();
+                self.apply_updates(&event.updates);
+                self.synced = true;
+                self.verify()
+            }
+            EventType::Update => {
+                // Updates received before the snapshot can't be placed in a book.
+                if !self.synced {
+                    return Err(BookError::NotSynced);
+                }
+                self.apply_updates(&event.updates);
+                self.verify()
+            }
+        }
+    }
+
+    fn apply_updates(&mut self, updates: &[Level2Update]) {
+        for update in updates.iter() {
+            let levels = match update.side {
+                Side::Bid => &mut self.bids,
+                Side::Offer => &mut self.asks,
+            };
+
+            if update.new_quantity == 0.0 {
+                levels.remove(&update.price_level);
+            } else {
+                levels.insert(update.price_level, update.new_quantity);
+            }
+        }
+    }
+
+    /// Checks the book is usable, a crossed book means updates were missed.
+    pub fn verify(&self) -> Result<(), BookError> {
+        if !self.synced {
+            return Err(BookError::NotSynced);
+        }
+
+        let (best_bid, best_ask) = match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => (bid, ask),
+            (None, _) => return Err(BookError::EmptySide(Side::Bid)),
+            (_, None) => return Err(BookError::EmptySide(Side::Offer)),
+        };
+
+        if best_bid.0 >= best_ask.0 {
+            return Err(BookError::Crossed {
+                best_bid: best_bid.0,
+                best_ask: best_ask.0,
+            });
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn is_synced(&self) -> bool {
+        self.synced
+    }
+
+    /// Highest bid as (price, size).
+    pub fn best_bid(&self) -> Option<(f64, f64)> {
+        self.bids
+            .iter()
+            .next_back()
+            .and_then(|(price, size)| price.to_f64().map(|p| (p, *size)))
+    }
+
+    /// Lowest ask as (price, size).
+    pub fn best_ask(&self) -> Option<(f64, f64)> {
+        self.asks
+            .iter()
+            .next()
+            .and_then(|(price, size)| price.to_f64().map(|p| (p, *size)))
+    }
+
+    pub fn mid_price(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some((bid, _)), Some((ask, _))) => Some((bid + ask) / 2.0),
+            _ => None,
+        }
+    }
+
+    pub fn spread(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some((bid, _)), Some((ask, _))) => Some(ask - bid),
+            _ => None,
+        }
+    }
+
+    pub fn spread_bps(&self) -> Option<f64> {
+        match (self.spread(), self.mid_price()) {
+            (Some(spread), Some(mid)) if mid > 0.0 => Some(spread / mid * 10_000.0),
+            _ => None,
+        }
+    }
+
+    /// Base size resting on each side within `bps` basis points of the mid price.
+    pub fn depth_within_bps(&self, bps: f64) -> Option<BookDepth> {
+        let mid = self.mid_price()?;
+        let offset = mid * bps / 10_000.0;
+        let bid_floor = Decimal::try_from(mid - offset).ok()?;
+        let ask_ceiling = Decimal::try_from(mid + offset).ok()?;
+
+        let bid_size = self.bids.range(bid_floor..).map(|(_, size)| size).sum();
+        let ask_size = self.asks.range(..=ask_ceiling).map(|(_, size)| size).sum();
+
+        Some(BookDepth { bid_size, ask_size })
+    }
+
+    pub fn imbalance(&self, bps: f64) -> Option<f64> {
+        self.depth_within_bps(bps)?.imbalance()
+    }
+}
+
+impl fmt::Display for OrderBook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} Bid: {:?}, Ask: {:?}, Spread(bps): {:?}, Imbalance(10bps): {:?}",
+            self.product_id,
+            self.best_bid(),
+            self.best_ask(),
+            self.spread_bps(),
+            self.imbalance(10.0)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRODUCT_ID: &str = "BTC-USDC";
+
+    // Prices are in cents so they stay exact as decimals.
+    fn event(event_type: EventType, updates: &[(Side, i64, f64)]) -> Level2Event {
+        Level2Event {
+            event_type,
+            product_id: PRODUCT_ID.to_string(),
+            updates: updates
+                .iter()
+                .map(|(side, cents, new_quantity)| Level2Update {
+                    side: *side,
+                    price_level: Decimal::new(*cents, 2),
+                    new_quantity: *new_quantity,
+                })
+                .collect(),
+        }
+    }
+
+    fn synced_book() -> OrderBook {
+        let mut book = OrderBook::new(PRODUCT_ID);
+        let snapshot = event(
+            EventType::Snapshot,
+            &[
+                (Side::Bid, 9_900, 2.0),
+                (Side::Bid, 9_800, 3.0),
+                (Side::Offer, 10_100, 1.0),
+                (Side::Offer, 10_200, 4.0),
+            ],
+        );
+        assert_eq!(book.apply_event(&snapshot), Ok(()));
+        book
+    }
+
+    #[test]
+    fn updates_wait_for_the_snapshot() {
+        let mut book = OrderBook::new(PRODUCT_ID);
+        let update = event(EventType::Update, &[(Side::Bid, 9_900, 1.0)]);
+
+        assert_eq!(book.apply_event(&update), Err(BookError::NotSynced));
+        assert_eq!(book.verify(), Err(BookError::NotSynced));
+        assert_eq!(book.best_bid(), None);
+    }
+
+    #[test]
+    fn prices_from_the_snapshot() {
+        let book = synced_book();
+
+        assert_eq!(book.best_bid(), Some((99.0, 2.0)));
+        assert_eq!(book.best_ask(), Some((101.0, 1.0)));
+        assert_eq!(book.mid_price(), Some(100.0));
+        assert_eq!(book.spread(), Some(2.0));
+        assert_eq!(book.spread_bps(), Some(200.0));
+
+        // 150 bps of 100 reaches the 99 bid and the 101 ask only.
+        let depth = book.depth_within_bps(150.0).unwrap();
+        assert_eq!(depth.bid_size, 2.0);
+        assert_eq!(depth.ask_size, 1.0);
+        assert_eq!(book.imbalance(150.0), Some(1.0 / 3.0));
+    }
+
+    #[test]
+    fn updates_replace_and_remove_levels() {
+        let mut book = synced_book();
+        let update = event(
+            EventType::Update,
+            &[(Side::Offer, 10_100, 0.0), (Side::Bid, 10_050, 1.5)],
+        );
+
+        assert_eq!(book.apply_event(&update), Ok(()));
+        assert_eq!(book.best_bid(), Some((100.5, 1.5)));
+        assert_eq!(book.best_ask(), Some((102.0, 4.0)));
+        assert_eq!(book.verify(), Ok(()));
+    }
+
+    #[test]
+    fn other_products_are_ignored() {
+        let mut book = synced_book();
+        let mut update = event(EventType::Update, &[(Side::Bid, 10_500, 1.0)]);
+        update.product_id = "ETH-USDC".to_string();
+
+        assert_eq!(book.apply_event(&update), Ok(()));
+        assert_eq!(book.best_bid(), Some((99.0, 2.0)));
+    }
+
+    #[test]
+    fn update_that_crosses_the_book_is_an_error() {
+        let mut book = synced_book();
+        let update = event(EventType::Update, &[(Side::Bid, 10_100, 1.0)]);
+        let crossed = Err(BookError::Crossed {
+            best_bid: 101.0,
+            best_ask: 101.0,
+        });
+
+        assert_eq!(book.apply_event(&update), crossed);
+        assert_eq!(book.verify(), crossed);
+
+        // Still crossed until a snapshot replaces the book.
+        let update = event(EventType::Update, &[(Side::Bid, 9_950, 1.0)]);
+        assert_eq!(book.apply_event(&update), crossed);
+    }
+
+    #[test]
+    fn detects_an_empty_side() {
+        let mut book = synced_book();
+        let update = event(
+            EventType::Update,
+            &[(Side::Offer, 10_100, 0.0), (Side::Offer, 10_200, 0.0)],
+        );
+
+        assert_eq!(
+            book.apply_event(&update),
+            Err(BookError::EmptySide(Side::Offer))
+        );
+        assert_eq!(book.mid_price(), None);
+
+        let empty = event(EventType::Snapshot, &[(Side::Offer, 10_100, 1.0)]);
+        assert_eq!(
+            book.apply_event(&empty),
+            Err(BookError::EmptySide(Side::Bid))
+        );
+    }
+
+    #[test]
+    fn reset_waits_for_a_new_snapshot() {
+        let mut book = synced_book();
+        book.reset();
+
+        assert!(!book.is_synced());
+        assert_eq!(book.best_ask(), None);
+        assert_eq!(book.verify(), Err(BookError::NotSynced));
+    }
+}
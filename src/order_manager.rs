@@ -0,0 +1,163 @@
+use std::collections::{HashMap, HashSet};
+
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+
+use crate::{
+    account::BotAccount,
+    coin::CoinSymbol,
+    model::{account::Product, OrderStatus, TradeSide},
+};
+
+// A resting limit order, `tag` lets the owner map a fill back to whatever
+// placed it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LimitOrder {
+    pub side: TradeSide,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub tag: usize,
+}
+
+// A tracked order once coinbase has settled it. filled_size can be part of
+// the order when it was cancelled after a partial fill.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settlement {
+    pub order: LimitOrder,
+    pub status: OrderStatus,
+    pub filled_size: f64,
+    pub average_price: f64,
+}
+
+// Keeps track of the limit orders placed for a single product and reports
+// them back once coinbase has settled them. Cancelled orders stay tracked until
+// a poll confirms them, so a fill that beat the cancel is still reported.
+#[derive(Debug)]
+pub struct OrderManager {
+    symbol: CoinSymbol,
+    product: Product,
+    open: HashMap<String, LimitOrder>,
+    cancelling: HashSet<String>,
+}
+
+impl OrderManager {
+    pub fn new(symbol: CoinSymbol, product: Product) -> Self {
+        OrderManager {
+            symbol,
+            product,
+            open: HashMap::new(),
+            cancelling: HashSet::new(),
+        }
+    }
+
+    #[inline]
+    pub fn get_product(&self) -> &Product {
+        &self.product
+    }
+
+    pub fn place_limit(
+        &mut self,
+        account: &mut BotAccount,
+        order: LimitOrder,
+        post_only: bool,
+    ) -> bool {
+        match account.create_limit_order(
+            &self.product.product_id,
+            order.side,
+            order.size,
+            order.price,
+            post_only,
+        ) {
+            Some(order_id) => {
+                self.open.insert(order_id, order);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Orders missing from the open list are looked up one by one and every
+    // settled order is returned with its status, so the owner can put back
+    // whatever was waiting on one that didn't fill.
+    pub fn poll_fills(&mut self, account: &mut BotAccount) -> Vec<Settlement> {
+        if self.open.is_empty() {
+            return Vec::new();
+        }
+
+        let still_open: HashSet<String> = account
+            .get_open_orders(self.symbol)
+            .into_iter()
+            .map(|order| order.order_id)
+            .collect();
+
+        let mut settled = Vec::new();
+
+        for (order_id, order) in self.open.iter() {
+            if still_open.contains(order_id) {
+                continue;
+            }
+
+            let summary = match account.get_order(order_id) {
+                Some(summary) => summary,
+                None => continue,
+            };
+            let status = summary.status;
+
+            match status {
+                OrderStatus::Filled => (),
+                OrderStatus::Cancelled if self.cancelling.contains(order_id) => (),
+                OrderStatus::Cancelled | OrderStatus::Expired | OrderStatus::Failed => {
+                    println!(
+                        "{} {} limit order {} at {}: {:?}",
+                        self.product.product_id, order.side, order_id, order.price, status
+                    );
+                }
+                _ => continue,
+            }
+
+            // Falls back to the order itself if a fill comes back without
+            // its size.
+            let (filled_size, average_price) = match status {
+                _ if summary.filled_size > 0.0 => {
+                    (summary.filled_size, summary.average_filled_price)
+                }
+                OrderStatus::Filled => (
+                    order.size.to_f64().unwrap_or(0.0),
+                    order.price.to_f64().unwrap_or(0.0),
+                ),
+                _ => (0.0, 0.0),
+            };
+
+            settled.push((
+                order_id.clone(),
+                Settlement {
+                    order: *order,
+                    status,
+                    filled_size,
+                    average_price,
+                },
+            ));
+        }
+
+        for (order_id, _) in settled.iter() {
+            self.open.remove(order_id);
+            self.cancelling.remove(order_id);
+        }
+
+        settled
+            .into_iter()
+            .map(|(_, settlement)| settlement)
+            .collect()
+    }
+
+    pub fn cancel_all(&mut self, account: &mut BotAccount) {
+        let order_ids: Vec<String> = self
+            .open
+            .keys()
+            .filter(|order_id| !self.cancelling.contains(*order_id))
+            .cloned()
+            .collect();
+
+        account.cancel_orders(&order_ids);
+        self.cancelling.extend(order_ids);
+    }
+}
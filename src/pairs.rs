@@ -0,0 +1,660 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    str::FromStr,
+};
+
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    account::{get_product_candle, BotAccount},
+    coin::CoinSymbol,
+    model::{account::Product, event::Candlestick, TradeSide},
+};
+
+const DEFAULT_PAIR_WINDOW: usize = 100;
+const DEFAULT_ENTRY_Z: f64 = 2.0;
+const DEFAULT_EXIT_Z: f64 = 0.0;
+
+// Coinbase returns at most 300 candles per request.
+const MAX_PAIR_WINDOW: usize = 300;
+const CANDLE_SECONDS: i64 = 300;
+
+fn default_window() -> usize {
+    DEFAULT_PAIR_WINDOW
+}
+
+fn default_entry_z() -> f64 {
+    DEFAULT_ENTRY_Z
+}
+
+fn default_exit_z() -> f64 {
+    DEFAULT_EXIT_Z
+}
+
+// Trades the spread ln(a) - hedge_ratio * ln(b) over the last `window`
+// candles. quote_amount is the USDC size of the a leg, the b leg is sized
+// by the hedge ratio.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PairConfig {
+    pub a: String,
+    pub b: String,
+    #[serde(default = "default_window")]
+    pub window: usize,
+    #[serde(default = "default_entry_z")]
+    pub entry_z: f64,
+    #[serde(default = "default_exit_z")]
+    pub exit_z: f64,
+    pub quote_amount: f64,
+}
+
+impl PairConfig {
+    pub fn symbols(&self) -> Option<(CoinSymbol, CoinSymbol)> {
+        let a = CoinSymbol::from_str(&self.a.to_uppercase()).ok()?;
+        let b = CoinSymbol::from_str(&self.b.to_uppercase()).ok()?;
+
+        (a != b).then_some((a, b))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.window < 2 || self.window > MAX_PAIR_WINDOW {
+            return Err(format!(
+                "pair window must be 2 - {}, got {}",
+                MAX_PAIR_WINDOW, self.window
+            ));
+        }
+
+        if self.entry_z <= 0.0 || self.exit_z >= self.entry_z {
+            return Err(format!(
+                "pair z scores need 0 < entry_z and exit_z < entry_z, got {} and {}",
+                self.entry_z, self.exit_z
+            ));
+        }
+
+        if self.quote_amount <= 0.0 {
+            return Err(format!(
+                "pair quote_amount must be positive, got {}",
+                self.quote_amount
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PairValue {
+    pub hedge_ratio: f64,
+    pub z_score: f64,
+}
+
+// Least squares fit of ln(a) on ln(b) over a rolling window, the z score is
+// the latest residual over the standard deviation of the residuals.
+#[derive(Debug)]
+pub struct PairStats {
+    window: usize,
+    values: VecDeque<(f64, f64)>,
+}
+
+impl PairStats {
+    pub fn new(window: usize) -> Self {
+        PairStats {
+            window,
+            values: VecDeque::with_capacity(window),
+        }
+    }
+
+    pub fn update(&mut self, a: f64, b: f64) -> Option<PairValue> {
+        if a <= 0.0 || b <= 0.0 {
+            return None;
+        }
+
+        if self.values.len() == self.window {
+            self.values.pop_front();
+        }
+        self.values.push_back((a.ln(), b.ln()));
+
+        if self.values.len() < self.window {
+            return None;
+        }
+
+        let n = self.values.len() as f64;
+        let mean_a = self.values.iter().map(|(a, _)| a).sum::<f64>() / n;
+        let mean_b = self.values.iter().map(|(_, b)| b).sum::<f64>() / n;
+
+        let (covariance, variance) =
+            self.values
+                .iter()
+                .fold((0.0, 0.0), |(covariance, variance), (a, b)| {
+                    (
+                        covariance + (a - mean_a) * (b - mean_b),
+                        variance + (b - mean_b).powi(2),
+                    )
+                });
+        if variance <= 0.0 {
+            return None;
+        }
+
+        let hedge_ratio = covariance / variance;
+        let intercept = mean_a - hedge_ratio * mean_b;
+        let residual = |(a, b): &(f64, f64)| a - intercept - hedge_ratio * b;
+
+        let deviation = (self.values.iter().map(|v| residual(v).powi(2)).sum::<f64>() / n).sqrt();
+        if deviation <= 0.0 {
+            return None;
+        }
+
+        let last = self.values.back().map(residual)?;
+
+        Some(PairValue {
+            hedge_ratio,
+            z_score: last / deviation,
+        })
+    }
+}
+
+// Base sizes held on each leg, a long spread holds a and has sold b.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PairPosition {
+    #[default]
+    Flat,
+    LongSpread {
+        a_size: f64,
+        b_size: f64,
+    },
+    ShortSpread {
+        a_size: f64,
+        b_size: f64,
+    },
+}
+
+#[derive(Debug)]
+struct PairLeg {
+    symbol: CoinSymbol,
+    product: Product,
+    pending: Option<Candlestick>,
+}
+
+impl PairLeg {
+    fn new(symbol: CoinSymbol, account: &BotAccount) -> Self {
+        PairLeg {
+            symbol,
+            product: account.get_product(symbol),
+            pending: None,
+        }
+    }
+
+    // Market buys are sized in quote like the rest of the bot's buys, so
+    // buying back a base size spends what it cost at `price` plus the fee.
+    fn buy_base(&self, size: f64, price: f64, account: &mut BotAccount) -> bool {
+        let quote = match self
+            .product
+            .round_quote(size * price / (1.0 - account.get_taker_fee()))
+        {
+            Some(quote) => quote,
+            None => return false,
+        };
+
+        account
+            .send_market_order(
+                &self.product.product_id,
+                TradeSide::Buy,
+                Some(quote.to_string()),
+                None,
+            )
+            .is_some()
+    }
+}
+
+#[derive(Debug)]
+pub struct PairTrader {
+    config: PairConfig,
+    a: PairLeg,
+    b: PairLeg,
+    stats: PairStats,
+    position: PairPosition,
+    last_start: i64,
+}
+
+impl PairTrader {
+    pub fn new(config: PairConfig, a: CoinSymbol, b: CoinSymbol, account: &BotAccount) -> Self {
+        let window = config.window;
+
+        let mut trader = PairTrader {
+            a: PairLeg::new(a, account),
+            b: PairLeg::new(b, account),
+            stats: PairStats::new(window),
+            position: PairPosition::Flat,
+            last_start: 0,
+            config,
+        };
+        trader.warmup(window);
+        trader.load_state();
+
+        trader
+    }
+
+    pub fn get_name(&self) -> String {
+        format!(
+            "{}/{}",
+            String::from(self.a.symbol),
+            String::from(self.b.symbol)
+        )
+    }
+
+    pub fn uses(&self, symbol: CoinSymbol) -> bool {
+        self.a.symbol == symbol || self.b.symbol == symbol
+    }
+
+    // Base size of the coin bought by the open position. The leg that was
+    // sold came out of the free balance and isn't held.
+    pub fn held(&self, symbol: CoinSymbol) -> f64 {
+        match self.position {
+            PairPosition::LongSpread { a_size, .. } if symbol == self.a.symbol => a_size,
+            PairPosition::ShortSpread { b_size, .. } if symbol == self.b.symbol => b_size,
+            _ => 0.0,
+        }
+    }
+
+    // Fills the window from the candles both legs have in common.
+    fn warmup(&mut self, window: usize) {
+        let now = chrono::Utc::now().timestamp();
+        let end = now - now.rem_euclid(CANDLE_SECONDS) - CANDLE_SECONDS;
+        let start = end - window as i64 * CANDLE_SECONDS;
+
+        let a_history = get_product_candle(self.a.symbol, start, end, "FIVE_MINUTE");
+        let b_history = get_product_candle(self.b.symbol, start, end, "FIVE_MINUTE");
+
+        for a_candle in a_history.candles.iter().rev() {
+            if let Some(b_candle) = b_history
+                .candles
+                .iter()
+                .find(|candle| candle.start == a_candle.start)
+            {
+                self.stats.update(a_candle.close, b_candle.close);
+                self.last_start = a_candle.start;
+            }
+        }
+    }
+
+    // Closed candles from the coin threads, the pair only updates once it
+    // has the same candle from both legs.
+    pub fn on_candle(&mut self, symbol: CoinSymbol, candle: Candlestick, account: &mut BotAccount) {
+        if candle.start <= self.last_start {
+            return;
+        }
+
+        if symbol == self.a.symbol {
+            self.a.pending = Some(candle);
+        } else if symbol == self.b.symbol {
+            self.b.pending = Some(candle);
+        }
+
+        let (a_candle, b_candle) = match (self.a.pending, self.b.pending) {
+            (Some(a), Some(b)) if a.start == b.start => (a, b),
+            // One leg moved on without the other, wait for the matching candle.
+            (Some(a), Some(b)) => {
+                if a.start < b.start {
+                    self.a.pending = None;
+                } else {
+                    self.b.pending = None;
+                }
+                return;
+            }
+            _ => return,
+        };
+
+        self.a.pending = None;
+        self.b.pending = None;
+        self.last_start = a_candle.start;
+
+        if let Some(value) = self.stats.update(a_candle.close, b_candle.close) {
+            println!(
+                "{} hedge ratio: {:.4}, z score: {:.2}, position: {:?}",
+                self.get_name(),
+                value.hedge_ratio,
+                value.z_score,
+                self.position
+            );
+            self.on_value(value, a_candle.close, b_candle.close, account);
+        }
+    }
+
+    fn on_value(&mut self, value: PairValue, a_price: f64, b_price: f64, account: &mut BotAccount) {
+        match self.position {
+            PairPosition::Flat if value.hedge_ratio <= 0.0 => (),
+            PairPosition::Flat if value.z_score <= -self.config.entry_z => {
+                self.open(true, value.hedge_ratio, a_price, b_price, account)
+            }
+            PairPosition::Flat if value.z_score >= self.config.entry_z => {
+                self.open(false, value.hedge_ratio, a_price, b_price, account)
+            }
+            PairPosition::LongSpread { .. } if value.z_score >= -self.config.exit_z => {
+                self.close(a_price, b_price, account)
+            }
+            PairPosition::ShortSpread { .. } if value.z_score <= self.config.exit_z => {
+                self.close(a_price, b_price, account)
+            }
+            _ => (),
+        }
+    }
+
+    // Sells the rich leg from holdings first, then buys the cheap one. If the
+    // buy fails the sell is bought back so the pair is never left on one leg,
+    // and if that fails too the sold leg is kept as the position to close.
+    fn open(
+        &mut self,
+        long_a: bool,
+        hedge_ratio: f64,
+        a_price: f64,
+        b_price: f64,
+        account: &mut BotAccount,
+    ) {
+        let a_quote = self.config.quote_amount;
+        let b_quote = self.config.quote_amount * hedge_ratio;
+
+        let (buy, buy_quote, buy_price, sell, sell_quote, sell_price) = if long_a {
+            (&self.a, a_quote, a_price, &self.b, b_quote, b_price)
+        } else {
+            (&self.b, b_quote, b_price, &self.a, a_quote, a_price)
+        };
+
+        let sell_size = match sell.product.round_base(sell_quote / sell_price) {
+            Some(size) if size.to_f64().unwrap_or(0.0) >= sell.product.base_min_size => size,
+            _ => {
+                println!("{} sell leg is under the minimum size", self.get_name());
+                return;
+            }
+        };
+        let buy_quote = match buy.product.round_quote(buy_quote) {
+            Some(quote) if quote.to_f64().unwrap_or(0.0) >= buy.product.quote_min_size => quote,
+            _ => {
+                println!("{} buy leg is under the minimum size", self.get_name());
+                return;
+            }
+        };
+
+        // Only coin the strategy and dca schedule aren't holding is sold.
+        let held = account.get_free_coin_balance(sell.symbol);
+        if held < sell_size.to_f64().unwrap_or(f64::MAX) {
+            println!(
+                "{} needs {} {} to sell, {} free",
+                self.get_name(),
+                sell_size,
+                String::from(sell.symbol),
+                held
+            );
+            return;
+        }
+
+        if account
+            .send_market_order(
+                &sell.product.product_id,
+                TradeSide::Sell,
+                None,
+                Some(sell_size.to_string()),
+            )
+            .is_none()
+        {
+            return;
+        }
+
+        if account
+            .send_market_order(
+                &buy.product.product_id,
+                TradeSide::Buy,
+                Some(buy_quote.to_string()),
+                None,
+            )
+            .is_none()
+        {
+            println!("{} buy leg failed, unwinding the sell", self.get_name());
+            let sell_size = sell_size.to_f64().unwrap_or(0.0);
+            if sell.buy_base(sell_size, sell_price, account) {
+                return;
+            }
+
+            self.position = if long_a {
+                PairPosition::LongSpread {
+                    a_size: 0.0,
+                    b_size: sell_size,
+                }
+            } else {
+                PairPosition::ShortSpread {
+                    a_size: sell_size,
+                    b_size: 0.0,
+                }
+            };
+            println!(
+                "{} failed to unwind the sell, holding {:?} until it closes",
+                self.get_name(),
+                self.position
+            );
+            self.save_state();
+            return;
+        }
+
+        let sell_size = sell_size.to_f64().unwrap_or(0.0);
+        let buy_size = buy
+            .product
+            .round_base(
+                buy_quote.to_f64().unwrap_or(0.0) / buy_price * (1.0 - account.get_taker_fee()),
+            )
+            .and_then(|size| size.to_f64())
+            .unwrap_or(0.0);
+
+        self.position = if long_a {
+            PairPosition::LongSpread {
+                a_size: buy_size,
+                b_size: sell_size,
+            }
+        } else {
+            PairPosition::ShortSpread {
+                a_size: sell_size,
+                b_size: buy_size,
+            }
+        };
+        println!("{} opened {:?}", self.get_name(), self.position);
+        self.save_state();
+    }
+
+    // Sells the leg that was bought and buys back the one that was sold.
+    fn close(&mut self, a_price: f64, b_price: f64, account: &mut BotAccount) {
+        let (buy_back, buy_size, buy_price, sell, sell_size) = match self.position {
+            PairPosition::LongSpread { a_size, b_size } => {
+                (&self.b, b_size, b_price, &self.a, a_size)
+            }
+            PairPosition::ShortSpread { a_size, b_size } => {
+                (&self.a, a_size, a_price, &self.b, b_size)
+            }
+            PairPosition::Flat => return,
+        };
+
+        let sent_sell = sell_size <= 0.0
+            || account
+                .send_market_order(
+                    &sell.product.product_id,
+                    TradeSide::Sell,
+                    None,
+                    Some(sell_size.to_string()),
+                )
+                .is_some();
+        let sent_buy = buy_size <= 0.0 || buy_back.buy_base(buy_size, buy_price, account);
+
+        // Keep the position until both legs are out, the next candle retries.
+        if !(sent_sell && sent_buy) {
+            println!(
+                "{} failed to close, sell: {}, buy back: {}",
+                self.get_name(),
+                sent_sell,
+                sent_buy
+            );
+            self.position = match self.position {
+                PairPosition::LongSpread { a_size, b_size } => PairPosition::LongSpread {
+                    a_size: if sent_sell { 0.0 } else { a_size },
+                    b_size: if sent_buy { 0.0 } else { b_size },
+                },
+                PairPosition::ShortSpread { a_size, b_size } => PairPosition::ShortSpread {
+                    a_size: if sent_buy { 0.0 } else { a_size },
+                    b_size: if sent_sell { 0.0 } else { b_size },
+                },
+                PairPosition::Flat => PairPosition::Flat,
+            };
+            self.save_state();
+            return;
+        }
+
+        println!(
+            "{} closed {:?} at {} / {}",
+            self.get_name(),
+            self.position,
+            a_price,
+            b_price
+        );
+        self.position = PairPosition::Flat;
+        self.save_state();
+    }
+
+    fn state_file(&self) -> String {
+        format!(
+            "pair_{}_{}_state.json",
+            String::from(self.a.symbol).to_lowercase(),
+            String::from(self.b.symbol).to_lowercase()
+        )
+    }
+
+    pub fn save_state(&self) {
+        let result = serde_json::to_string_pretty(&self.position)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(self.state_file(), json).map_err(|e| e.to_string()));
+
+        if let Err(e) = result {
+            println!("Failed to save {} state: {}", self.get_name(), e);
+        }
+    }
+
+    fn load_state(&mut self) {
+        let position = std::fs::read_to_string(self.state_file())
+            .ok()
+            .and_then(|json| serde_json::from_str::<PairPosition>(&json).ok());
+
+        if let Some(position) = position {
+            println!("Loaded {} position: {:?}", self.get_name(), position);
+            self.position = position;
+        }
+    }
+}
+
+// Saves what the traders hold of each coin into the coin's state, so the
+// coin's strategy leaves it out of its sells. Only coins whose holdings
+// changed since the last call are written.
+pub fn record_holdings(traders: &[PairTrader], recorded: &mut HashMap<CoinSymbol, f64>) {
+    let mut holdings: HashMap<CoinSymbol, f64> = HashMap::new();
+    for trader in traders.iter() {
+        for symbol in [trader.a.symbol, trader.b.symbol] {
+            *holdings.entry(symbol).or_default() += trader.held(symbol);
+        }
+    }
+
+    for (symbol, size) in holdings {
+        if recorded.get(&symbol) != Some(&size) {
+            BotAccount::save_pair_size(symbol, size);
+            recorded.insert(symbol, size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::assert_close;
+
+    // ln(a) runs 0, 2, 2 against ln(b) of 0, 1, 2.
+    fn feed(stats: &mut PairStats, a_scale: f64, b_scale: f64) -> Option<PairValue> {
+        [(0.0, 0.0), (2.0, 1.0), (2.0, 2.0)]
+            .iter()
+            .map(|&(a, b): &(f64, f64)| stats.update(a_scale * a.exp(), b_scale * b.exp()))
+            .last()
+            .flatten()
+    }
+
+    fn config() -> PairConfig {
+        PairConfig {
+            a: "eth".to_string(),
+            b: "btc".to_string(),
+            window: DEFAULT_PAIR_WINDOW,
+            entry_z: DEFAULT_ENTRY_Z,
+            exit_z: DEFAULT_EXIT_Z,
+            quote_amount: 50.0,
+        }
+    }
+
+    #[test]
+    fn fits_the_hedge_ratio_on_log_prices() {
+        let mut stats = PairStats::new(3);
+        let value = feed(&mut stats, 1.0, 1.0).unwrap();
+
+        // Covariance 2 over a variance of 2, with an intercept of 1/3 the
+        // residuals are -1/3, 2/3 and -1/3, a deviation of sqrt(2) / 3.
+        assert_close(value.hedge_ratio, 1.0);
+        assert_close(value.z_score, -1.0 / 2f64.sqrt());
+    }
+
+    #[test]
+    fn price_scale_only_moves_the_intercept() {
+        let mut stats = PairStats::new(3);
+        let value = feed(&mut stats, 1.0, 1.0).unwrap();
+
+        let mut scaled = PairStats::new(3);
+        let scaled_value = feed(&mut scaled, 3_000.0, 60_000.0).unwrap();
+
+        assert_close(scaled_value.hedge_ratio, value.hedge_ratio);
+        assert_close(scaled_value.z_score, value.z_score);
+    }
+
+    #[test]
+    fn waits_for_a_full_window_and_rolls_it() {
+        let mut stats = PairStats::new(3);
+        assert_eq!(stats.update(1.0, 1.0), None);
+        assert_eq!(stats.update(2.0f64.exp(), 1.0f64.exp()), None);
+        // Prices that can't be logged are skipped.
+        assert_eq!(stats.update(0.0, 5.0), None);
+        assert!(stats.update(2.0f64.exp(), 2.0f64.exp()).is_some());
+
+        // The first point drops out, leaving ln(a) 2, 2, 5 on ln(b) 1, 2, 3.
+        // The fit is 1.5 * ln(b) with residuals 0.5, -1 and 0.5.
+        let value = stats.update(5.0f64.exp(), 3.0f64.exp()).unwrap();
+        assert_close(value.hedge_ratio, 1.5);
+        assert_close(value.z_score, 0.5 / 0.5f64.sqrt());
+    }
+
+    #[test]
+    fn validates_the_config() {
+        assert!(config().validate().is_ok());
+
+        let bad = [
+            PairConfig {
+                window: 1,
+                ..config()
+            },
+            PairConfig {
+                window: MAX_PAIR_WINDOW + 1,
+                ..config()
+            },
+            PairConfig {
+                entry_z: 0.0,
+                ..config()
+            },
+            PairConfig {
+                exit_z: DEFAULT_ENTRY_Z,
+                ..config()
+            },
+            PairConfig {
+                quote_amount: 0.0,
+                ..config()
+            },
+        ];
+        for config in bad {
+            assert!(config.validate().is_err(), "{:?}", config);
+        }
+    }
+}
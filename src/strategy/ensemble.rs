@@ -0,0 +1,1208 @@
+use serde::Deserialize;
+
+use crate::{
+    indicators::{
+        adx::{Adx, ADX_THRESHOLD},
+        bollinger::Bollinger,
+        cross::{CrossEvent, Crossover},
+        donchian::Donchian,
+        ema::Ema,
+        keltner::Keltner,
+        macd::Macd,
+        mfi::Mfi,
+        moving_average::{MaType, MovingAverage},
+        obv::Obv,
+        parabolic_sar::ParabolicSar,
+        rsi::Rsi,
+        sma::Sma,
+        stoch::Stochastic,
+        stoch_rsi::StochRsi,
+        supertrend::SuperTrend,
+        volume_profile::VolumeProfile,
+        vwap::{Vwap, VwapPeriod},
+        Indicator,
+    },
+    model::event::Candlestick,
+    trading_bot::TradeSignal,
+};
+
+use super::Strategy;
+
+const RSI_OVERSOLD: f64 = 40.0;
+const RSI_OVERBROUGHT: f64 = 60.0;
+
+const STOCH_OVERSOLD: f64 = 20.0;
+const STOCH_OVERBOUGHT: f64 = 80.0;
+
+const MFI_OVERSOLD: f64 = 20.0;
+const MFI_OVERBOUGHT: f64 = 80.0;
+
+const VWAP_BAND_MULTIPLIER: f64 = 2.0;
+const VALUE_AREA: f64 = 0.7;
+
+const SAR_STEP: f64 = 0.02;
+const SAR_MAX_STEP: f64 = 0.2;
+
+// Macd and ema_position compare levels unless `cross_within` is set, then
+// they only signal for that many bars after a cross in the same direction.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignalConfig {
+    Macd {
+        fast: usize,
+        slow: usize,
+        signal: usize,
+        #[serde(default)]
+        cross_within: Option<usize>,
+        #[serde(default)]
+        average: MaType,
+    },
+    EmaPosition {
+        period: usize,
+        #[serde(default)]
+        cross_within: Option<usize>,
+        #[serde(default)]
+        average: MaType,
+    },
+    Rsi {
+        period: usize,
+        #[serde(default = "default_oversold")]
+        oversold: f64,
+        #[serde(default = "default_overbought")]
+        overbought: f64,
+    },
+    StochRsi {
+        rsi_period: usize,
+        stoch_period: usize,
+        d_period: usize,
+        #[serde(default = "default_cross_within")]
+        cross_within: usize,
+    },
+    // Buys when %K crosses above %D with %D oversold, sells on the opposite.
+    Stochastic {
+        k_period: usize,
+        k_smoothing: usize,
+        d_period: usize,
+        #[serde(default = "default_stoch_oversold")]
+        oversold: f64,
+        #[serde(default = "default_stoch_overbought")]
+        overbought: f64,
+        #[serde(default = "default_cross_within")]
+        cross_within: usize,
+    },
+    // Mean reversion, buys below the lower band and sells above the upper band.
+    Bollinger {
+        period: usize,
+        multiplier: f64,
+    },
+    // Breakout, buys above the upper channel and sells below the lower channel.
+    Keltner {
+        ema_period: usize,
+        atr_period: usize,
+        multiplier: f64,
+    },
+    // Breakout of the previous `period` candles high or low.
+    Donchian {
+        period: usize,
+    },
+    // Trend, buys above the vwap and sells below it. Without a period the
+    // vwap resets at the start of each UTC day.
+    Vwap {
+        #[serde(default)]
+        period: Option<usize>,
+        #[serde(default = "default_vwap_multiplier")]
+        multiplier: f64,
+    },
+    // Buys while on balance volume is above its ema and sells while below.
+    Obv {
+        ema_period: usize,
+    },
+    Mfi {
+        period: usize,
+        #[serde(default = "default_mfi_oversold")]
+        oversold: f64,
+        #[serde(default = "default_mfi_overbought")]
+        overbought: f64,
+    },
+    // Breakout, buys above the value area and sells below it.
+    VolumeProfile {
+        period: usize,
+        bins: usize,
+        #[serde(default = "default_value_area")]
+        value_area: f64,
+    },
+    // Follows the stronger of +DI and -DI while adx is above the threshold.
+    Adx {
+        period: usize,
+        #[serde(default = "default_adx_threshold")]
+        threshold: f64,
+    },
+    SuperTrend {
+        atr_period: usize,
+        multiplier: f64,
+    },
+    ParabolicSar {
+        #[serde(default = "default_sar_step")]
+        step: f64,
+        #[serde(default = "default_sar_max_step")]
+        max_step: f64,
+    },
+}
+
+// A period of 0 underflows or divides by zero in the indicators.
+fn check_period(name: &str, period: usize) -> Result<(), String> {
+    if period == 0 {
+        return Err(format!("{} must be at least 1", name));
+    }
+    Ok(())
+}
+
+fn check_positive(name: &str, value: f64) -> Result<(), String> {
+    if value.is_nan() || value <= 0.0 {
+        return Err(format!("{} must be positive, got {}", name, value));
+    }
+    Ok(())
+}
+
+impl SignalConfig {
+    fn validate(&self) -> Result<(), String> {
+        match *self {
+            SignalConfig::Macd {
+                fast,
+                slow,
+                signal,
+                cross_within,
+                average,
+            } => {
+                for period in [fast, slow, signal] {
+                    average.validate_period(period)?;
+                }
+                cross_within.map_or(Ok(()), |bars| check_period("macd cross_within", bars))
+            }
+            SignalConfig::EmaPosition {
+                period,
+                cross_within,
+                average,
+            } => {
+                average.validate_period(period)?;
+                cross_within.map_or(Ok(()), |bars| {
+                    check_period("ema_position cross_within", bars)
+                })
+            }
+            SignalConfig::Rsi { period, .. } => check_period("rsi period", period),
+            SignalConfig::StochRsi {
+                rsi_period,
+                stoch_period,
+                d_period,
+                cross_within,
+            } => {
+                check_period("stoch_rsi rsi_period", rsi_period)?;
+                check_period("stoch_rsi stoch_period", stoch_period)?;
+                check_period("stoch_rsi d_period", d_period)?;
+                check_period("stoch_rsi cross_within", cross_within)
+            }
+            SignalConfig::Stochastic {
+                k_period,
+                k_smoothing,
+                d_period,
+                cross_within,
+                ..
+            } => {
+                check_period("stochastic k_period", k_period)?;
+                check_period("stochastic k_smoothing", k_smoothing)?;
+                check_period("stochastic d_period", d_period)?;
+                check_period("stochastic cross_within", cross_within)
+            }
+            SignalConfig::Bollinger { period, multiplier } => {
+                check_period("bollinger period", period)?;
+                check_positive("bollinger multiplier", multiplier)
+            }
+            SignalConfig::Keltner {
+                ema_period,
+                atr_period,
+                multiplier,
+            } => {
+                check_period("keltner ema_period", ema_period)?;
+                check_period("keltner atr_period", atr_period)?;
+                check_positive("keltner multiplier", multiplier)
+            }
+            SignalConfig::Donchian { period } => check_period("donchian period", period),
+            SignalConfig::Vwap { period, multiplier } => {
+                period.map_or(Ok(()), |period| check_period("vwap period", period))?;
+                check_positive("vwap multiplier", multiplier)
+            }
+            SignalConfig::Obv { ema_period } => check_period("obv ema_period", ema_period),
+            SignalConfig::Mfi { period, .. } => check_period("mfi period", period),
+            SignalConfig::VolumeProfile {
+                period,
+                bins,
+                value_area,
+            } => {
+                check_period("volume_profile period", period)?;
+                check_period("volume_profile bins", bins)?;
+                check_positive("volume_profile value_area", value_area)
+            }
+            SignalConfig::Adx { period, .. } => check_period("adx period", period),
+            SignalConfig::SuperTrend {
+                atr_period,
+                multiplier,
+            } => {
+                check_period("super_trend atr_period", atr_period)?;
+                check_positive("super_trend multiplier", multiplier)
+            }
+            SignalConfig::ParabolicSar { step, max_step } => {
+                check_positive("parabolic_sar step", step)?;
+                check_positive("parabolic_sar max_step", max_step)
+            }
+        }
+    }
+}
+
+fn default_cross_within() -> usize {
+    1
+}
+
+fn default_oversold() -> f64 {
+    RSI_OVERSOLD
+}
+
+fn default_overbought() -> f64 {
+    RSI_OVERBROUGHT
+}
+
+fn default_stoch_oversold() -> f64 {
+    STOCH_OVERSOLD
+}
+
+fn default_stoch_overbought() -> f64 {
+    STOCH_OVERBOUGHT
+}
+
+fn default_mfi_oversold() -> f64 {
+    MFI_OVERSOLD
+}
+
+fn default_mfi_overbought() -> f64 {
+    MFI_OVERBOUGHT
+}
+
+fn default_vwap_multiplier() -> f64 {
+    VWAP_BAND_MULTIPLIER
+}
+
+fn default_value_area() -> f64 {
+    VALUE_AREA
+}
+
+fn default_adx_threshold() -> f64 {
+    ADX_THRESHOLD
+}
+
+fn default_sar_step() -> f64 {
+    SAR_STEP
+}
+
+fn default_sar_max_step() -> f64 {
+    SAR_MAX_STEP
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_confirm_bars() -> usize {
+    1
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WeightedSignalConfig {
+    #[serde(flatten)]
+    pub signal: SignalConfig,
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VoteRule {
+    // Every signal has to agree.
+    Unanimous,
+    // More than half the signals have to agree.
+    Majority,
+    // Weighted average of the signals, buy = 1 and sell = -1, has to be past
+    // the threshold.
+    Weighted { threshold: f64 },
+}
+
+impl VoteRule {
+    // Signals paired with their weights. The weighted score has to be strictly
+    // past the threshold, so all holds never trade even with a threshold of 0.
+    fn tally(self, votes: &[(TradeSignal, f64)]) -> TradeSignal {
+        let total = votes.len();
+        let buys = votes
+            .iter()
+            .filter(|(vote, _)| *vote == TradeSignal::Buy)
+            .count();
+        let sells = votes
+            .iter()
+            .filter(|(vote, _)| *vote == TradeSignal::Sell)
+            .count();
+
+        if total == 0 {
+            return TradeSignal::Hold;
+        }
+
+        match self {
+            VoteRule::Unanimous if buys == total => TradeSignal::Buy,
+            VoteRule::Unanimous if sells == total => TradeSignal::Sell,
+            VoteRule::Majority if buys * 2 > total => TradeSignal::Buy,
+            VoteRule::Majority if sells * 2 > total => TradeSignal::Sell,
+            VoteRule::Weighted { threshold } => {
+                let total_weight: f64 = votes.iter().map(|(_, w)| w).sum();
+                if total_weight <= 0.0 {
+                    return TradeSignal::Hold;
+                }
+
+                let score: f64 = votes
+                    .iter()
+                    .map(|(vote, w)| match vote {
+                        TradeSignal::Buy => *w,
+                        TradeSignal::Sell => -w,
+                        TradeSignal::Hold => 0.0,
+                    })
+                    .sum::<f64>()
+                    / total_weight;
+
+                if score > threshold {
+                    TradeSignal::Buy
+                } else if score < -threshold {
+                    TradeSignal::Sell
+                } else {
+                    TradeSignal::Hold
+                }
+            }
+            _ => TradeSignal::Hold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnsembleConfig {
+    pub signals: Vec<WeightedSignalConfig>,
+    pub rule: VoteRule,
+    // Number of bars in a row the vote has to hold before it is acted on.
+    #[serde(default = "default_confirm_bars")]
+    pub confirm_bars: usize,
+    #[serde(default)]
+    pub volume_filter: Option<VolumeFilterConfig>,
+}
+
+impl EnsembleConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        for signal in self.signals.iter() {
+            signal.signal.validate()?;
+        }
+
+        if let Some(filter) = self.volume_filter.as_ref() {
+            check_period("volume_filter period", filter.period)?;
+            check_positive("volume_filter multiplier", filter.multiplier)?;
+        }
+
+        let total_weight: f64 = self.signals.iter().map(|signal| signal.weight).sum();
+        match self.rule {
+            VoteRule::Weighted { .. } if total_weight <= 0.0 => Err(format!(
+                "weighted ensemble needs a positive total weight, got {}",
+                total_weight
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+// A buy vote only counts when the candle's volume is at least `multiplier`
+// times the average volume of the previous `period` candles.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct VolumeFilterConfig {
+    pub period: usize,
+    pub multiplier: f64,
+}
+
+#[derive(Debug)]
+struct VolumeFilter {
+    average: Sma,
+    multiplier: f64,
+    confirmed: bool,
+}
+
+impl VolumeFilter {
+    fn new(config: &VolumeFilterConfig) -> Self {
+        VolumeFilter {
+            average: Sma::new(config.period),
+            multiplier: config.multiplier,
+            confirmed: false,
+        }
+    }
+
+    fn update(&mut self, candle: &Candlestick) {
+        // Compare against the average before this candle is added.
+        self.confirmed = self
+            .average
+            .get_value()
+            .is_some_and(|average| candle.volume >= average * self.multiplier);
+        self.average.update(candle.volume);
+    }
+
+    fn reset(&mut self) {
+        self.average.reset();
+        self.confirmed = false;
+    }
+}
+
+#[derive(Debug)]
+enum IndicatorSignal {
+    Macd {
+        macd: Macd,
+        cross: Crossover,
+        cross_within: Option<usize>,
+    },
+    EmaPosition {
+        ema: Box<dyn MovingAverage>,
+        close: f64,
+        cross: Crossover,
+        cross_within: Option<usize>,
+    },
+    Rsi {
+        rsi: Rsi,
+        oversold: f64,
+        overbought: f64,
+    },
+    StochRsi {
+        stoch_rsi: StochRsi,
+        cross: Crossover,
+        cross_within: usize,
+    },
+    Stochastic {
+        stochastic: Stochastic,
+        cross: Crossover,
+        oversold: f64,
+        overbought: f64,
+        cross_within: usize,
+    },
+    Bollinger(Bollinger),
+    Keltner {
+        keltner: Keltner,
+        close: f64,
+    },
+    Donchian {
+        donchian: Donchian,
+        close: f64,
+    },
+    Vwap {
+        vwap: Vwap,
+        close: f64,
+    },
+    Obv {
+        obv: Obv,
+        ema: Ema,
+    },
+    Mfi {
+        mfi: Mfi,
+        oversold: f64,
+        overbought: f64,
+    },
+    VolumeProfile {
+        profile: VolumeProfile,
+        close: f64,
+    },
+    Adx {
+        adx: Adx,
+        threshold: f64,
+    },
+    SuperTrend(SuperTrend),
+    ParabolicSar(ParabolicSar),
+}
+
+impl IndicatorSignal {
+    fn new(config: &SignalConfig) -> Self {
+        match *config {
+            SignalConfig::Macd {
+                fast,
+                slow,
+                signal,
+                cross_within,
+                average,
+            } => IndicatorSignal::Macd {
+                macd: Macd::new(average, fast, slow, signal),
+                cross: Crossover::new(),
+                cross_within,
+            },
+            SignalConfig::EmaPosition {
+                period,
+                cross_within,
+                average,
+            } => IndicatorSignal::EmaPosition {
+                ema: average.build(period),
+                close: 0.0,
+                cross: Crossover::new(),
+                cross_within,
+            },
+            SignalConfig::Rsi {
+                period,
+                oversold,
+                overbought,
+            } => IndicatorSignal::Rsi {
+                rsi: Rsi::new(period),
+                oversold,
+                overbought,
+            },
+            SignalConfig::StochRsi {
+                rsi_period,
+                stoch_period,
+                d_period,
+                cross_within,
+            } => IndicatorSignal::StochRsi {
+                stoch_rsi: StochRsi::new(rsi_period, stoch_period, d_period),
+                cross: Crossover::new(),
+                cross_within,
+            },
+            SignalConfig::Stochastic {
+                k_period,
+                k_smoothing,
+                d_period,
+                oversold,
+                overbought,
+                cross_within,
+            } => IndicatorSignal::Stochastic {
+                stochastic: Stochastic::new(k_period, k_smoothing, d_period),
+                cross: Crossover::new(),
+                oversold,
+                overbought,
+                cross_within,
+            },
+            SignalConfig::Bollinger { period, multiplier } => {
+                IndicatorSignal::Bollinger(Bollinger::new(period, multiplier))
+            }
+            SignalConfig::Keltner {
+                ema_period,
+                atr_period,
+                multiplier,
+            } => IndicatorSignal::Keltner {
+                keltner: Keltner::new(ema_period, atr_period, multiplier),
+                close: 0.0,
+            },
+            SignalConfig::Donchian { period } => IndicatorSignal::Donchian {
+                donchian: Donchian::new(period),
+                close: 0.0,
+            },
+            SignalConfig::Vwap { period, multiplier } => IndicatorSignal::Vwap {
+                vwap: Vwap::new(
+                    period.map_or(VwapPeriod::Session, VwapPeriod::Rolling),
+                    multiplier,
+                ),
+                close: 0.0,
+            },
+            SignalConfig::Obv { ema_period } => IndicatorSignal::Obv {
+                obv: Obv::new(),
+                ema: Ema::new(ema_period),
+            },
+            SignalConfig::Mfi {
+                period,
+                oversold,
+                overbought,
+            } => IndicatorSignal::Mfi {
+                mfi: Mfi::new(period),
+                oversold,
+                overbought,
+            },
+            SignalConfig::VolumeProfile {
+                period,
+                bins,
+                value_area,
+            } => IndicatorSignal::VolumeProfile {
+                profile: VolumeProfile::new(period, bins, value_area),
+                close: 0.0,
+            },
+            SignalConfig::Adx { period, threshold } => IndicatorSignal::Adx {
+                adx: Adx::new(period),
+                threshold,
+            },
+            SignalConfig::SuperTrend {
+                atr_period,
+                multiplier,
+            } => IndicatorSignal::SuperTrend(SuperTrend::new(atr_period, multiplier)),
+            SignalConfig::ParabolicSar { step, max_step } => {
+                IndicatorSignal::ParabolicSar(ParabolicSar::new(step, max_step))
+            }
+        }
+    }
+
+    fn warmup_period(&self) -> usize {
+        match self {
+            IndicatorSignal::Macd { macd, .. } => macd.warmup_period(),
+            IndicatorSignal::EmaPosition { ema, .. } => ema.warmup_period(),
+            IndicatorSignal::Rsi { rsi, .. } => rsi.warmup_period(),
+            IndicatorSignal::StochRsi { stoch_rsi, .. } => stoch_rsi.warmup_period(),
+            IndicatorSignal::Stochastic { stochastic, .. } => stochastic.warmup_period(),
+            IndicatorSignal::Bollinger(bollinger) => bollinger.warmup_period(),
+            IndicatorSignal::Keltner { keltner, .. } => keltner.warmup_period(),
+            // The breakout compares against the channel before the latest candle.
+            IndicatorSignal::Donchian { donchian, .. } => donchian.warmup_period() + 1,
+            IndicatorSignal::Vwap { vwap, .. } => vwap.warmup_period(),
+            // The ema only starts once obv has a value.
+            IndicatorSignal::Obv { obv, ema } => obv.warmup_period() + ema.warmup_period() - 1,
+            IndicatorSignal::Mfi { mfi, .. } => mfi.warmup_period(),
+            IndicatorSignal::VolumeProfile { profile, .. } => profile.warmup_period(),
+            IndicatorSignal::Adx { adx, .. } => adx.warmup_period(),
+            IndicatorSignal::SuperTrend(supertrend) => supertrend.warmup_period(),
+            IndicatorSignal::ParabolicSar(sar) => sar.warmup_period(),
+        }
+    }
+
+    fn update(&mut self, candle: &Candlestick) {
+        match self {
+            IndicatorSignal::Macd { macd, cross, .. } => {
+                if let Some(value) = macd.on_candle(candle) {
+                    cross.update(value.macd, value.signal);
+                }
+            }
+            IndicatorSignal::EmaPosition {
+                ema, close, cross, ..
+            } => {
+                *close = candle.close;
+                if let Some(value) = ema.on_candle(candle) {
+                    cross.update(*close, value);
+                }
+            }
+            IndicatorSignal::Rsi { rsi, .. } => {
+                rsi.on_candle(candle);
+            }
+            IndicatorSignal::StochRsi {
+                stoch_rsi, cross, ..
+            } => {
+                if let Some(value) = stoch_rsi.on_candle(candle) {
+                    cross.update(value.k, value.d);
+                }
+            }
+            IndicatorSignal::Stochastic {
+                stochastic, cross, ..
+            } => {
+                if let Some(value) = stochastic.on_candle(candle) {
+                    cross.update(value.k, value.d);
+                }
+            }
+            IndicatorSignal::Bollinger(bollinger) => {
+                bollinger.on_candle(candle);
+            }
+            IndicatorSignal::Keltner { keltner, close } => {
+                keltner.on_candle(candle);
+                *close = candle.close;
+            }
+            IndicatorSignal::Donchian { donchian, close } => {
+                donchian.on_candle(candle);
+                *close = candle.close;
+            }
+            IndicatorSignal::Vwap { vwap, close } => {
+                vwap.on_candle(candle);
+                *close = candle.close;
+            }
+            IndicatorSignal::Obv { obv, ema } => {
+                if let Some(value) = obv.on_candle(candle) {
+                    ema.update(value);
+                }
+            }
+            IndicatorSignal::Mfi { mfi, .. } => {
+                mfi.on_candle(candle);
+            }
+            IndicatorSignal::VolumeProfile { profile, close } => {
+                profile.on_candle(candle);
+                *close = candle.close;
+            }
+            IndicatorSignal::Adx { adx, .. } => {
+                adx.on_candle(candle);
+            }
+            IndicatorSignal::SuperTrend(supertrend) => {
+                supertrend.on_candle(candle);
+            }
+            IndicatorSignal::ParabolicSar(sar) => {
+                sar.on_candle(candle);
+            }
+        }
+    }
+
+    // The closes are left as they are, each is set again by the next candle.
+    fn reset(&mut self) {
+        match self {
+            IndicatorSignal::Macd { macd, cross, .. } => {
+                macd.reset();
+                cross.reset();
+            }
+            IndicatorSignal::EmaPosition { ema, cross, .. } => {
+                ema.reset();
+                cross.reset();
+            }
+            IndicatorSignal::Rsi { rsi, .. } => rsi.reset(),
+            IndicatorSignal::StochRsi {
+                stoch_rsi, cross, ..
+            } => {
+                stoch_rsi.reset();
+                cross.reset();
+            }
+            IndicatorSignal::Stochastic {
+                stochastic, cross, ..
+            } => {
+                stochastic.reset();
+                cross.reset();
+            }
+            IndicatorSignal::Bollinger(bollinger) => bollinger.reset(),
+            IndicatorSignal::Keltner { keltner, .. } => keltner.reset(),
+            IndicatorSignal::Donchian { donchian, .. } => donchian.reset(),
+            IndicatorSignal::Vwap { vwap, .. } => vwap.reset(),
+            IndicatorSignal::Obv { obv, ema } => {
+                obv.reset();
+                ema.reset();
+            }
+            IndicatorSignal::Mfi { mfi, .. } => mfi.reset(),
+            IndicatorSignal::VolumeProfile { profile, .. } => profile.reset(),
+            IndicatorSignal::Adx { adx, .. } => adx.reset(),
+            IndicatorSignal::SuperTrend(supertrend) => supertrend.reset(),
+            IndicatorSignal::ParabolicSar(sar) => sar.reset(),
+        }
+    }
+
+    fn get_signal(&self) -> TradeSignal {
+        match self {
+            IndicatorSignal::Macd {
+                macd,
+                cross,
+                cross_within,
+            } => match macd.get_value() {
+                Some(value) => confirm_cross(
+                    compare_signal(value.macd, value.signal),
+                    cross,
+                    *cross_within,
+                ),
+                None => TradeSignal::Hold,
+            },
+            IndicatorSignal::EmaPosition {
+                ema,
+                close,
+                cross,
+                cross_within,
+            } => match ema.get_value() {
+                Some(value) => confirm_cross(compare_signal(*close, value), cross, *cross_within),
+                None => TradeSignal::Hold,
+            },
+            IndicatorSignal::Rsi {
+                rsi,
+                oversold,
+                overbought,
+            } => match rsi.get_value() {
+                Some(value) if value < *oversold => TradeSignal::Buy,
+                Some(value) if value > *overbought => TradeSignal::Sell,
+                _ => TradeSignal::Hold,
+            },
+            // Only the bars just after %K crosses %D give a signal.
+            IndicatorSignal::StochRsi {
+                cross,
+                cross_within,
+                ..
+            } => match cross.crossed_within(*cross_within) {
+                Some(c) if c.event == CrossEvent::CrossUp => TradeSignal::Buy,
+                Some(c) if c.event == CrossEvent::CrossDown => TradeSignal::Sell,
+                _ => TradeSignal::Hold,
+            },
+            IndicatorSignal::Stochastic {
+                stochastic,
+                cross,
+                oversold,
+                overbought,
+                cross_within,
+            } => match (stochastic.get_value(), cross.crossed_within(*cross_within)) {
+                (Some(value), Some(c)) if c.event == CrossEvent::CrossUp && value.d < *oversold => {
+                    TradeSignal::Buy
+                }
+                (Some(value), Some(c))
+                    if c.event == CrossEvent::CrossDown && value.d > *overbought =>
+                {
+                    TradeSignal::Sell
+                }
+                _ => TradeSignal::Hold,
+            },
+            IndicatorSignal::Bollinger(bollinger) => match bollinger.get_value() {
+                Some(value) if value.percent_b < 0.0 => TradeSignal::Buy,
+                Some(value) if value.percent_b > 1.0 => TradeSignal::Sell,
+                _ => TradeSignal::Hold,
+            },
+            IndicatorSignal::Keltner { keltner, close } => match keltner.get_value() {
+                Some(value) if *close > value.upper => TradeSignal::Buy,
+                Some(value) if *close < value.lower => TradeSignal::Sell,
+                _ => TradeSignal::Hold,
+            },
+            IndicatorSignal::Donchian { donchian, close } => match donchian.get_previous() {
+                Some(value) if *close > value.upper => TradeSignal::Buy,
+                Some(value) if *close < value.lower => TradeSignal::Sell,
+                _ => TradeSignal::Hold,
+            },
+            IndicatorSignal::Vwap { vwap, close } => match vwap.get_value() {
+                Some(value) => compare_signal(*close, value.vwap),
+                None => TradeSignal::Hold,
+            },
+            IndicatorSignal::Obv { obv, ema } => match (obv.get_value(), ema.get_value()) {
+                (Some(obv), Some(ema)) => compare_signal(obv, ema),
+                _ => TradeSignal::Hold,
+            },
+            IndicatorSignal::Mfi {
+                mfi,
+                oversold,
+                overbought,
+            } => match mfi.get_value() {
+                Some(value) if value < *oversold => TradeSignal::Buy,
+                Some(value) if value > *overbought => TradeSignal::Sell,
+                _ => TradeSignal::Hold,
+            },
+            IndicatorSignal::VolumeProfile { profile, close } => match profile.get_value() {
+                Some(value) if *close > value.value_area_high => TradeSignal::Buy,
+                Some(value) if *close < value.value_area_low => TradeSignal::Sell,
+                _ => TradeSignal::Hold,
+            },
+            IndicatorSignal::Adx { adx, threshold } => match adx.get_value() {
+                Some(value) if value.adx >= *threshold => {
+                    compare_signal(value.plus_di, value.minus_di)
+                }
+                _ => TradeSignal::Hold,
+            },
+            IndicatorSignal::SuperTrend(supertrend) => match supertrend.get_value() {
+                Some(value) if value.is_uptrend => TradeSignal::Buy,
+                Some(_) => TradeSignal::Sell,
+                None => TradeSignal::Hold,
+            },
+            IndicatorSignal::ParabolicSar(sar) => match sar.get_value() {
+                Some(value) if value.is_long => TradeSignal::Buy,
+                Some(_) => TradeSignal::Sell,
+                None => TradeSignal::Hold,
+            },
+        }
+    }
+}
+
+// Keeps a level signal only if a cross in the same direction was recent.
+fn confirm_cross(
+    signal: TradeSignal,
+    cross: &Crossover,
+    cross_within: Option<usize>,
+) -> TradeSignal {
+    let bars = match cross_within {
+        Some(bars) => bars,
+        None => return signal,
+    };
+
+    match (signal, cross.crossed_within(bars)) {
+        (TradeSignal::Buy, Some(c)) if c.event == CrossEvent::CrossUp => TradeSignal::Buy,
+        (TradeSignal::Sell, Some(c)) if c.event == CrossEvent::CrossDown => TradeSignal::Sell,
+        _ => TradeSignal::Hold,
+    }
+}
+
+fn compare_signal(value: f64, reference: f64) -> TradeSignal {
+    if value > reference {
+        TradeSignal::Buy
+    } else if value < reference {
+        TradeSignal::Sell
+    } else {
+        TradeSignal::Hold
+    }
+}
+
+// Combines any number of indicator signals with a vote rule.
+#[derive(Debug)]
+pub struct EnsembleStrategy {
+    signals: Vec<(IndicatorSignal, f64)>,
+    rule: VoteRule,
+    confirm_bars: usize,
+    warmup: usize,
+    volume_filter: Option<VolumeFilter>,
+    vote: TradeSignal,
+    vote_bars: usize,
+}
+
+impl EnsembleStrategy {
+    pub fn new(config: &EnsembleConfig) -> Self {
+        let signals: Vec<(IndicatorSignal, f64)> = config
+            .signals
+            .iter()
+            .map(|s| (IndicatorSignal::new(&s.signal), s.weight))
+            .collect();
+
+        let warmup = signals
+            .iter()
+            .map(|(s, _)| s.warmup_period())
+            .max()
+            .unwrap_or(0)
+            .max(config.volume_filter.map_or(0, |f| f.period + 1));
+
+        EnsembleStrategy {
+            signals,
+            rule: config.rule,
+            confirm_bars: config.confirm_bars.max(1),
+            warmup: warmup + config.confirm_bars.saturating_sub(1),
+            volume_filter: config.volume_filter.as_ref().map(VolumeFilter::new),
+            vote: TradeSignal::Hold,
+            vote_bars: 0,
+        }
+    }
+
+    fn count_votes(&self) -> TradeSignal {
+        let votes: Vec<(TradeSignal, f64)> = self
+            .signals
+            .iter()
+            .map(|(s, w)| (s.get_signal(), *w))
+            .collect();
+
+        self.rule.tally(&votes)
+    }
+}
+
+impl Strategy for EnsembleStrategy {
+    fn warmup_period(&self) -> usize {
+        self.warmup
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) {
+        for (signal, _) in self.signals.iter_mut() {
+            signal.update(candle);
+        }
+
+        if let Some(filter) = self.volume_filter.as_mut() {
+            filter.update(candle);
+        }
+
+        let vote = match self.count_votes() {
+            TradeSignal::Buy if self.volume_filter.as_ref().is_some_and(|f| !f.confirmed) => {
+                TradeSignal::Hold
+            }
+            vote => vote,
+        };
+
+        if vote == self.vote {
+            self.vote_bars += 1;
+        } else {
+            self.vote = vote;
+            self.vote_bars = 1;
+        }
+    }
+
+    fn get_signal(&self) -> TradeSignal {
+        if self.vote_bars >= self.confirm_bars {
+            self.vote
+        } else {
+            TradeSignal::Hold
+        }
+    }
+
+    fn reset(&mut self) {
+        for (signal, _) in self.signals.iter_mut() {
+            signal.reset();
+        }
+        if let Some(filter) = self.volume_filter.as_mut() {
+            filter.reset();
+        }
+        self.vote = TradeSignal::Hold;
+        self.vote_bars = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use TradeSignal::{Buy, Hold, Sell};
+
+    fn votes(signals: &[TradeSignal]) -> Vec<(TradeSignal, f64)> {
+        signals.iter().map(|signal| (*signal, 1.0)).collect()
+    }
+
+    #[test]
+    fn unanimous_and_majority() {
+        assert_eq!(VoteRule::Unanimous.tally(&votes(&[Buy, Buy])), Buy);
+        assert_eq!(VoteRule::Unanimous.tally(&votes(&[Buy, Hold])), Hold);
+        assert_eq!(VoteRule::Majority.tally(&votes(&[Sell, Sell, Buy])), Sell);
+        assert_eq!(VoteRule::Majority.tally(&votes(&[Buy, Sell])), Hold);
+        assert_eq!(VoteRule::Majority.tally(&[]), Hold);
+    }
+
+    #[test]
+    fn weighted_threshold_is_strict() {
+        let rule = VoteRule::Weighted { threshold: 0.5 };
+        assert_eq!(rule.tally(&[(Buy, 3.0), (Sell, 1.0)]), Hold);
+        assert_eq!(rule.tally(&[(Buy, 3.0), (Hold, 1.0)]), Buy);
+        assert_eq!(rule.tally(&[(Sell, 3.0), (Hold, 1.0)]), Sell);
+    }
+
+    #[test]
+    fn all_holds_never_trade() {
+        let rule = VoteRule::Weighted { threshold: 0.0 };
+        assert_eq!(rule.tally(&votes(&[Hold, Hold])), Hold);
+        assert_eq!(rule.tally(&votes(&[Buy, Sell])), Hold);
+        assert_eq!(rule.tally(&votes(&[Buy, Hold])), Buy);
+    }
+
+    #[test]
+    fn zero_weight_holds() {
+        let rule = VoteRule::Weighted { threshold: 0.0 };
+        assert_eq!(rule.tally(&[(Buy, 0.0), (Buy, 0.0)]), Hold);
+    }
+
+    #[test]
+    fn rejects_a_non_positive_total_weight() {
+        let config = |weights: &[f64]| EnsembleConfig {
+            signals: weights
+                .iter()
+                .map(|weight| WeightedSignalConfig {
+                    signal: SignalConfig::Donchian { period: 20 },
+                    weight: *weight,
+                })
+                .collect(),
+            rule: VoteRule::Weighted { threshold: 0.5 },
+            confirm_bars: 1,
+            volume_filter: None,
+        };
+
+        assert!(config(&[1.0, 0.5]).validate().is_ok());
+        assert!(config(&[0.0, 0.0]).validate().is_err());
+        assert!(config(&[1.0, -2.0]).validate().is_err());
+        assert!(config(&[]).validate().is_err());
+    }
+
+    fn ensemble(signal: SignalConfig) -> EnsembleConfig {
+        EnsembleConfig {
+            signals: vec![WeightedSignalConfig {
+                signal,
+                weight: 1.0,
+            }],
+            rule: VoteRule::Majority,
+            confirm_bars: 1,
+            volume_filter: None,
+        }
+    }
+
+    #[test]
+    fn rejects_a_zero_period_on_every_signal() {
+        let signals = [
+            SignalConfig::Rsi {
+                period: 0,
+                oversold: RSI_OVERSOLD,
+                overbought: RSI_OVERBROUGHT,
+            },
+            SignalConfig::StochRsi {
+                rsi_period: 14,
+                stoch_period: 0,
+                d_period: 3,
+                cross_within: 1,
+            },
+            SignalConfig::Stochastic {
+                k_period: 14,
+                k_smoothing: 0,
+                d_period: 3,
+                oversold: STOCH_OVERSOLD,
+                overbought: STOCH_OVERBOUGHT,
+                cross_within: 1,
+            },
+            SignalConfig::Bollinger {
+                period: 0,
+                multiplier: 2.0,
+            },
+            SignalConfig::Keltner {
+                ema_period: 20,
+                atr_period: 0,
+                multiplier: 2.0,
+            },
+            SignalConfig::Donchian { period: 0 },
+            SignalConfig::Vwap {
+                period: Some(0),
+                multiplier: VWAP_BAND_MULTIPLIER,
+            },
+            SignalConfig::Obv { ema_period: 0 },
+            SignalConfig::Mfi {
+                period: 0,
+                oversold: MFI_OVERSOLD,
+                overbought: MFI_OVERBOUGHT,
+            },
+            SignalConfig::VolumeProfile {
+                period: 0,
+                bins: 24,
+                value_area: VALUE_AREA,
+            },
+            SignalConfig::Adx {
+                period: 0,
+                threshold: ADX_THRESHOLD,
+            },
+            SignalConfig::SuperTrend {
+                atr_period: 0,
+                multiplier: 3.0,
+            },
+        ];
+
+        for signal in signals {
+            let result = ensemble(signal.clone()).validate();
+            assert!(result.is_err(), "{:?} was accepted", signal);
+        }
+    }
+
+    #[test]
+    fn rejects_non_positive_multipliers() {
+        let signals = [
+            SignalConfig::Bollinger {
+                period: 20,
+                multiplier: 0.0,
+            },
+            SignalConfig::Keltner {
+                ema_period: 20,
+                atr_period: 10,
+                multiplier: -1.0,
+            },
+            SignalConfig::SuperTrend {
+                atr_period: 10,
+                multiplier: 0.0,
+            },
+            SignalConfig::ParabolicSar {
+                step: 0.0,
+                max_step: SAR_MAX_STEP,
+            },
+        ];
+
+        for signal in signals {
+            let result = ensemble(signal.clone()).validate();
+            assert!(result.is_err(), "{:?} was accepted", signal);
+        }
+
+        let mut filtered = ensemble(SignalConfig::Donchian { period: 20 });
+        filtered.volume_filter = Some(VolumeFilterConfig {
+            period: 0,
+            multiplier: 1.5,
+        });
+        assert!(filtered.validate().is_err());
+    }
+
+    #[test]
+    fn accepts_the_defaults() {
+        let signals = [
+            SignalConfig::Keltner {
+                ema_period: 20,
+                atr_period: 10,
+                multiplier: 2.0,
+            },
+            SignalConfig::Vwap {
+                period: None,
+                multiplier: VWAP_BAND_MULTIPLIER,
+            },
+            SignalConfig::ParabolicSar {
+                step: SAR_STEP,
+                max_step: SAR_MAX_STEP,
+            },
+        ];
+
+        for signal in signals {
+            assert_eq!(ensemble(signal).validate(), Ok(()));
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{
+    indicators::{
+        cross::{CrossEvent, Crossover},
+        ichimoku::Ichimoku,
+        Indicator,
+    },
+    model::event::Candlestick,
+    trading_bot::TradeSignal,
+};
+
+use super::Strategy;
+
+// Number of bars after tenkan crosses above kijun that a buy is still allowed.
+const MAX_CROSS_PERIOD: usize = 3;
+
+// Buys on a recent tenkan/kijun cross up with price above the cloud, the
+// chikou span above the price it is drawn against and a bullish cloud ahead.
+// Sells once price closes under the kijun. The stop goes on the kijun, or
+// the bottom of the cloud when the kijun isn't under price.
+#[derive(Debug)]
+pub struct IchimokuStrategy {
+    ichimoku: Ichimoku,
+    tk_cross: Crossover,
+}
+
+impl IchimokuStrategy {
+    pub fn new(tenkan: usize, kijun: usize, senkou_b: usize, displacement: usize) -> Self {
+        IchimokuStrategy {
+            ichimoku: Ichimoku::new(tenkan, kijun, senkou_b, displacement),
+            tk_cross: Crossover::new(),
+        }
+    }
+}
+
+impl Strategy for IchimokuStrategy {
+    fn warmup_period(&self) -> usize {
+        self.ichimoku.warmup_period()
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) {
+        if let Some(value) = self.ichimoku.on_candle(candle) {
+            self.tk_cross.update(value.tenkan, value.kijun);
+        }
+    }
+
+    fn get_signal(&self) -> TradeSignal {
+        let value = match self.ichimoku.get_value() {
+            Some(value) => value,
+            None => return TradeSignal::Hold,
+        };
+        let close = value.chikou;
+        let recent_cross_up = self
+            .tk_cross
+            .crossed_within(MAX_CROSS_PERIOD)
+            .is_some_and(|cross| cross.event == CrossEvent::CrossUp);
+
+        if recent_cross_up
+            && value.tenkan > value.kijun
+            && close > value.cloud_top()
+            && value.chikou > value.chikou_reference
+            && value.leading_a > value.leading_b
+        {
+            TradeSignal::Buy
+        } else if close < value.kijun {
+            TradeSignal::Sell
+        } else {
+            TradeSignal::Hold
+        }
+    }
+
+    fn get_stop(&self, candle: &Candlestick) -> Option<f64> {
+        let value = self.ichimoku.get_value()?;
+
+        [value.kijun, value.cloud_bottom()]
+            .into_iter()
+            .find(|level| *level < candle.close)
+    }
+
+    fn reset(&mut self) {
+        self.ichimoku.reset();
+        self.tk_cross.reset();
+    }
+}
@@ -0,0 +1,133 @@
+use serde::Deserialize;
+
+use crate::{
+    indicators::{
+        pivot::{PivotKind, PivotPoints},
+        swing::SwingPoints,
+        Indicator,
+    },
+    model::event::Candlestick,
+};
+
+const SWING_STRENGTH: usize = 2;
+const SWING_COUNT: usize = 5;
+const RESISTANCE_ATR: f64 = 1.0;
+const STOP_ATR: f64 = 0.5;
+
+fn default_swing_strength() -> usize {
+    SWING_STRENGTH
+}
+
+fn default_swing_count() -> usize {
+    SWING_COUNT
+}
+
+fn default_resistance_atr() -> f64 {
+    RESISTANCE_ATR
+}
+
+fn default_stop_atr() -> f64 {
+    STOP_ATR
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct LevelsConfig {
+    // Pivot levels from the previous day, only swing levels are used when None.
+    #[serde(default)]
+    pub pivots: Option<PivotKind>,
+    #[serde(default = "default_swing_strength")]
+    pub swing_strength: usize,
+    #[serde(default = "default_swing_count")]
+    pub swing_count: usize,
+    // Buys are skipped when resistance is closer than this many atrs.
+    #[serde(default = "default_resistance_atr")]
+    pub resistance_atr: f64,
+    // Stops go this many atrs under the nearest support.
+    #[serde(default = "default_stop_atr")]
+    pub stop_atr: f64,
+}
+
+// Support and resistance from pivot points and recent swing highs and lows.
+#[derive(Debug)]
+pub struct SupportResistance {
+    pivots: Option<PivotPoints>,
+    swings: SwingPoints,
+    resistance_atr: f64,
+    stop_atr: f64,
+    close: f64,
+}
+
+impl SupportResistance {
+    pub fn new(config: &LevelsConfig) -> Self {
+        SupportResistance {
+            pivots: config.pivots.map(PivotPoints::new),
+            swings: SwingPoints::new(config.swing_strength, config.swing_count),
+            resistance_atr: config.resistance_atr,
+            stop_atr: config.stop_atr,
+            close: 0.0,
+        }
+    }
+
+    pub fn on_candle(&mut self, candle: &Candlestick) {
+        if let Some(pivots) = self.pivots.as_mut() {
+            pivots.on_candle(candle);
+        }
+        self.swings.on_candle(candle);
+        self.close = candle.close;
+    }
+
+    pub fn reset(&mut self) {
+        if let Some(pivots) = self.pivots.as_mut() {
+            pivots.reset();
+        }
+        self.swings.reset();
+        self.close = 0.0;
+    }
+
+    #[inline]
+    pub fn uses_pivots(&self) -> bool {
+        self.pivots.is_some()
+    }
+
+    pub fn set_previous_day(&mut self, day: &Candlestick) {
+        if let Some(pivots) = self.pivots.as_mut() {
+            pivots.set_previous_day(day);
+        }
+    }
+
+    fn levels(&self) -> impl Iterator<Item = f64> + '_ {
+        let pivot_levels = self
+            .pivots
+            .as_ref()
+            .and_then(|pivots| pivots.get_value())
+            .into_iter()
+            .flat_map(|levels| levels.levels());
+
+        pivot_levels
+            .chain(self.swings.get_highs())
+            .chain(self.swings.get_lows())
+    }
+
+    pub fn nearest_resistance(&self, price: f64) -> Option<f64> {
+        self.levels()
+            .filter(|level| *level > price)
+            .min_by(|a, b| a.total_cmp(b))
+    }
+
+    pub fn nearest_support(&self, price: f64) -> Option<f64> {
+        self.levels()
+            .filter(|level| *level < price)
+            .max_by(|a, b| a.total_cmp(b))
+    }
+
+    // False when the last close sits just under resistance.
+    pub fn allows_entry(&self, atr: f64) -> bool {
+        self.nearest_resistance(self.close)
+            .is_none_or(|resistance| resistance - self.close >= atr * self.resistance_atr)
+    }
+
+    pub fn get_stop(&self, price: f64, atr: f64) -> Option<f64> {
+        self.nearest_support(price)
+            .map(|support| support - atr * self.stop_atr)
+    }
+}
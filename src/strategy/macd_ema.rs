@@ -0,0 +1,110 @@
+use crate::{
+    indicators::{
+        cross::{CrossEvent, Crossover},
+        macd::Macd,
+        moving_average::{MaType, MovingAverage},
+        Indicator,
+    },
+    model::event::Candlestick,
+    trading_bot::TradeSignal,
+};
+
+use super::Strategy;
+
+// Number of bars after a macd cross up that a buy is still allowed.
+const MAX_CROSS_PERIOD: usize = 3;
+
+// Buys when price is above its ema and the macd line has recently crossed
+// above the signal line. Either average can be swapped for another type.
+#[derive(Debug)]
+pub struct MacdEmaStrategy {
+    macd: Macd,
+    macd_cross: Crossover,
+    price_ema: Box<dyn MovingAverage>,
+    close: f64,
+}
+
+impl MacdEmaStrategy {
+    pub fn new(
+        fast: usize,
+        slow: usize,
+        signal: usize,
+        ema: usize,
+        macd_average: MaType,
+        price_average: MaType,
+    ) -> Self {
+        MacdEmaStrategy {
+            macd: Macd::new(macd_average, fast, slow, signal),
+            macd_cross: Crossover::new(),
+            price_ema: price_average.build(ema),
+            close: 0.0,
+        }
+    }
+
+    fn get_macd_signal(&self) -> TradeSignal {
+        let value = match self.macd.get_value() {
+            Some(value) => value,
+            None => return TradeSignal::Hold,
+        };
+        let recent_cross_up = self
+            .macd_cross
+            .crossed_within(MAX_CROSS_PERIOD)
+            .is_some_and(|cross| cross.event == CrossEvent::CrossUp);
+
+        if value.macd > value.signal && recent_cross_up {
+            TradeSignal::Buy
+        } else if value.macd < value.signal {
+            TradeSignal::Sell
+        } else {
+            TradeSignal::Hold
+        }
+    }
+
+    fn get_ema_signal(&self, price: f64) -> TradeSignal {
+        match self.price_ema.get_value() {
+            Some(current_ema) if current_ema < price => TradeSignal::Buy,
+            Some(current_ema) if current_ema > price => TradeSignal::Sell,
+            _ => TradeSignal::Hold,
+        }
+    }
+}
+
+impl Strategy for MacdEmaStrategy {
+    fn warmup_period(&self) -> usize {
+        self.macd
+            .warmup_period()
+            .max(self.price_ema.warmup_period())
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) {
+        if let Some(value) = self.macd.on_candle(candle) {
+            self.macd_cross.update(value.macd, value.signal);
+        }
+        self.price_ema.on_candle(candle);
+        self.close = candle.close;
+    }
+
+    fn get_signal(&self) -> TradeSignal {
+        if !self.macd.is_ready() || !self.price_ema.is_ready() {
+            return TradeSignal::Hold;
+        }
+
+        let ema_signal = self.get_ema_signal(self.close);
+        let macd_signal = self.get_macd_signal();
+
+        if ema_signal == TradeSignal::Buy && macd_signal == TradeSignal::Buy {
+            TradeSignal::Buy
+        } else if ema_signal == TradeSignal::Sell && macd_signal == TradeSignal::Sell {
+            TradeSignal::Sell
+        } else {
+            TradeSignal::Hold
+        }
+    }
+
+    fn reset(&mut self) {
+        self.macd.reset();
+        self.macd_cross.reset();
+        self.price_ema.reset();
+        self.close = 0.0;
+    }
+}
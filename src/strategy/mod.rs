@@ -0,0 +1,196 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::{
+    indicators::moving_average::MaType, model::event::Candlestick, trading_bot::TradeSignal,
+};
+
+use self::{
+    ensemble::{EnsembleConfig, EnsembleStrategy},
+    ichimoku::IchimokuStrategy,
+    macd_ema::MacdEmaStrategy,
+};
+
+pub mod ensemble;
+pub mod ichimoku;
+pub mod levels;
+pub mod macd_ema;
+pub mod pattern_filter;
+pub mod regime;
+
+pub trait Strategy: fmt::Debug + Send {
+    // Number of closed candles needed before the signal can be trusted.
+    fn warmup_period(&self) -> usize;
+
+    fn on_candle(&mut self, candle: &Candlestick);
+
+    // Signal for the most recent closed candle.
+    fn get_signal(&self) -> TradeSignal;
+
+    // Stop loss price for a new long position, the atr stop is used when None.
+    fn get_stop(&self, _candle: &Candlestick) -> Option<f64> {
+        None
+    }
+
+    // Forgets every candle seen so far, used before replaying history.
+    fn reset(&mut self);
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StrategyConfig {
+    MacdEma {
+        fast: usize,
+        slow: usize,
+        signal: usize,
+        ema: usize,
+        // Average used for the macd lines and for the price filter.
+        #[serde(default)]
+        macd_average: MaType,
+        #[serde(default)]
+        price_average: MaType,
+    },
+    Ensemble(EnsembleConfig),
+    Ichimoku {
+        #[serde(default = "default_tenkan")]
+        tenkan: usize,
+        #[serde(default = "default_kijun")]
+        kijun: usize,
+        #[serde(default = "default_senkou_b")]
+        senkou_b: usize,
+        #[serde(default = "default_displacement")]
+        displacement: usize,
+    },
+}
+
+fn default_tenkan() -> usize {
+    9
+}
+
+fn default_kijun() -> usize {
+    26
+}
+
+fn default_senkou_b() -> usize {
+    52
+}
+
+fn default_displacement() -> usize {
+    26
+}
+
+impl Default for StrategyConfig {
+    fn default() -> Self {
+        StrategyConfig::MacdEma {
+            fast: 12,
+            slow: 21,
+            signal: 9,
+            ema: 20,
+            macd_average: MaType::Ema,
+            price_average: MaType::Ema,
+        }
+    }
+}
+
+impl StrategyConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            StrategyConfig::MacdEma {
+                fast,
+                slow,
+                signal,
+                ema,
+                macd_average,
+                price_average,
+            } => {
+                for period in [*fast, *slow, *signal] {
+                    macd_average.validate_period(period)?;
+                }
+                price_average.validate_period(*ema)
+            }
+            StrategyConfig::Ensemble(config) => config.validate(),
+            // The lines are built on longer and longer windows, and the cloud
+            // has to be drawn at least one candle ahead.
+            StrategyConfig::Ichimoku {
+                tenkan,
+                kijun,
+                senkou_b,
+                displacement,
+            } => {
+                if *tenkan == 0 || tenkan >= kijun || kijun >= senkou_b {
+                    return Err(format!(
+                        "ichimoku needs 1 <= tenkan < kijun < senkou_b, got {}, {} and {}",
+                        tenkan, kijun, senkou_b
+                    ));
+                }
+                if *displacement == 0 {
+                    return Err("ichimoku displacement must be at least 1".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn build(&self) -> Box<dyn Strategy> {
+        match self {
+            StrategyConfig::MacdEma {
+                fast,
+                slow,
+                signal,
+                ema,
+                macd_average,
+                price_average,
+            } => Box::new(MacdEmaStrategy::new(
+                *fast,
+                *slow,
+                *signal,
+                *ema,
+                *macd_average,
+                *price_average,
+            )),
+            StrategyConfig::Ensemble(config) => Box::new(EnsembleStrategy::new(config)),
+            StrategyConfig::Ichimoku {
+                tenkan,
+                kijun,
+                senkou_b,
+                displacement,
+            } => Box::new(IchimokuStrategy::new(
+                *tenkan,
+                *kijun,
+                *senkou_b,
+                *displacement,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ichimoku(
+        tenkan: usize,
+        kijun: usize,
+        senkou_b: usize,
+        displacement: usize,
+    ) -> StrategyConfig {
+        StrategyConfig::Ichimoku {
+            tenkan,
+            kijun,
+            senkou_b,
+            displacement,
+        }
+    }
+
+    #[test]
+    fn ichimoku_periods_have_to_increase() {
+        assert_eq!(ichimoku(9, 26, 52, 26).validate(), Ok(()));
+        assert_eq!(ichimoku(1, 2, 3, 1).validate(), Ok(()));
+
+        assert!(ichimoku(0, 26, 52, 26).validate().is_err());
+        assert!(ichimoku(26, 26, 52, 26).validate().is_err());
+        assert!(ichimoku(9, 52, 26, 26).validate().is_err());
+        assert!(ichimoku(9, 26, 52, 0).validate().is_err());
+    }
+}
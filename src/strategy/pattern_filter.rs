@@ -0,0 +1,107 @@
+use serde::Deserialize;
+
+use crate::{
+    indicators::{
+        pattern::{Pattern, PatternBias, PatternDetector},
+        Indicator,
+    },
+    model::event::Candlestick,
+    trading_bot::TradeSignal,
+};
+
+use super::Strategy;
+
+const PATTERN_WITHIN: usize = 3;
+const MIN_STRENGTH: f64 = 0.5;
+
+fn default_within() -> usize {
+    PATTERN_WITHIN
+}
+
+fn default_min_strength() -> f64 {
+    MIN_STRENGTH
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PatternFilterConfig {
+    // Number of bars a pattern still confirms an entry for.
+    #[serde(default = "default_within")]
+    pub within: usize,
+    #[serde(default = "default_min_strength")]
+    pub min_strength: f64,
+    // Patterns that count as a confirmation, any bullish pattern when empty.
+    #[serde(default)]
+    pub patterns: Vec<Pattern>,
+}
+
+// Wraps a strategy and only lets its buys through when a bullish candlestick
+// pattern formed within the last few bars. Sells are passed through.
+#[derive(Debug)]
+pub struct PatternFilter {
+    strategy: Box<dyn Strategy>,
+    detector: PatternDetector,
+    within: usize,
+    min_strength: f64,
+    patterns: Vec<Pattern>,
+    bars_since_pattern: Option<usize>,
+}
+
+impl PatternFilter {
+    pub fn new(strategy: Box<dyn Strategy>, config: &PatternFilterConfig) -> Self {
+        PatternFilter {
+            strategy,
+            detector: PatternDetector::new(),
+            within: config.within,
+            min_strength: config.min_strength,
+            patterns: config.patterns.clone(),
+            bars_since_pattern: None,
+        }
+    }
+
+    fn is_confirmed(&self) -> bool {
+        self.bars_since_pattern
+            .is_some_and(|bars_ago| bars_ago < self.within)
+    }
+}
+
+impl Strategy for PatternFilter {
+    fn warmup_period(&self) -> usize {
+        self.strategy
+            .warmup_period()
+            .max(self.detector.warmup_period())
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) {
+        self.strategy.on_candle(candle);
+        self.detector.on_candle(candle);
+
+        let confirmed = self.detector.get_patterns().iter().any(|event| {
+            event.bias == PatternBias::Bullish
+                && event.strength >= self.min_strength
+                && (self.patterns.is_empty() || self.patterns.contains(&event.pattern))
+        });
+
+        self.bars_since_pattern = if confirmed {
+            Some(0)
+        } else {
+            self.bars_since_pattern.map(|bars_ago| bars_ago + 1)
+        };
+    }
+
+    fn get_signal(&self) -> TradeSignal {
+        match self.strategy.get_signal() {
+            TradeSignal::Buy if !self.is_confirmed() => TradeSignal::Hold,
+            signal => signal,
+        }
+    }
+
+    fn get_stop(&self, candle: &Candlestick) -> Option<f64> {
+        self.strategy.get_stop(candle)
+    }
+
+    fn reset(&mut self) {
+        self.strategy.reset();
+        self.detector.reset();
+        self.bars_since_pattern = None;
+    }
+}
@@ -0,0 +1,82 @@
+use serde::Deserialize;
+
+use crate::{
+    indicators::{
+        adx::{Adx, ADX_THRESHOLD},
+        Indicator,
+    },
+    model::event::Candlestick,
+    trading_bot::TradeSignal,
+};
+
+use super::Strategy;
+
+const ADX_PERIOD: usize = 14;
+
+fn default_period() -> usize {
+    ADX_PERIOD
+}
+
+fn default_threshold() -> f64 {
+    ADX_THRESHOLD
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct RegimeFilterConfig {
+    #[serde(default = "default_period")]
+    pub period: usize,
+    #[serde(default = "default_threshold")]
+    pub threshold: f64,
+}
+
+// Wraps a strategy and turns its buys into holds while adx says the market
+// is ranging. Sells are passed through so positions can still be closed.
+#[derive(Debug)]
+pub struct RegimeFilter {
+    strategy: Box<dyn Strategy>,
+    adx: Adx,
+    threshold: f64,
+}
+
+impl RegimeFilter {
+    pub fn new(strategy: Box<dyn Strategy>, config: &RegimeFilterConfig) -> Self {
+        RegimeFilter {
+            strategy,
+            adx: Adx::new(config.period),
+            threshold: config.threshold,
+        }
+    }
+
+    fn is_trending(&self) -> bool {
+        self.adx
+            .get_value()
+            .is_some_and(|value| value.adx >= self.threshold)
+    }
+}
+
+impl Strategy for RegimeFilter {
+    fn warmup_period(&self) -> usize {
+        self.strategy.warmup_period().max(self.adx.warmup_period())
+    }
+
+    fn on_candle(&mut self, candle: &Candlestick) {
+        self.strategy.on_candle(candle);
+        self.adx.on_candle(candle);
+    }
+
+    fn get_signal(&self) -> TradeSignal {
+        match self.strategy.get_signal() {
+            TradeSignal::Buy if !self.is_trending() => TradeSignal::Hold,
+            signal => signal,
+        }
+    }
+
+    fn get_stop(&self, candle: &Candlestick) -> Option<f64> {
+        self.strategy.get_stop(candle)
+    }
+
+    fn reset(&mut self) {
+        self.strategy.reset();
+        self.adx.reset();
+    }
+}
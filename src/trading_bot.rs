@@ -1,13 +1,11 @@
-use std::collections::VecDeque;
-
 use crate::{
-    indicators::{
-        atr::Atr,
-        ema::Ema,
-        macd::{self, Macd},
-        rsi::Rsi,
-    },
+    config::SymbolConfig,
+    indicators::{atr::Atr, Indicator},
     model::event::Candlestick,
+    strategy::{
+        levels::SupportResistance, pattern_filter::PatternFilter, regime::RegimeFilter, Strategy,
+    },
+    transform::CandleTransform,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -17,81 +15,43 @@ pub enum TradeSignal {
     Hold,
 }
 
-const RSI_OVERSOLD: f64 = 40.0;
-const RSI_OVERBROUGHT: f64 = 60.0;
-
-const MAX_CROSS_PERIOD: usize = 3;
-
 const ATR_MODIFIER: f64 = 1.5;
 
-#[derive(Debug)]
-#[allow(dead_code)]
-pub struct TradingIndicator {
-    macd: Macd,
-    price_ema: Ema, // rsi: Rsi,
-}
-
-// Used to use macd and rsi.
-// Now use macd and ema cross.
-
-impl TradingIndicator {
-    pub fn new() -> Self {
-        let macd = Macd::new(12, 21, 9);
-        // let rsi = Rsi::new(14);
-        let price_ema = Ema::new(20);
-
-        TradingIndicator { macd, price_ema }
-    }
-
-    pub fn update(&mut self, current_price: f64) {
-        self.macd.update(current_price);
-        self.price_ema.update(current_price);
-    }
-
-    pub fn get_macd_signal(&self) -> TradeSignal {
-        let macd_line = self.macd.get_macd_line();
-        let macd_signal = self.macd.get_signal_line();
-
-        if macd_line > macd_signal {
-            TradeSignal::Buy
-        } else if macd_line < macd_signal {
-            TradeSignal::Sell
-        } else {
-            TradeSignal::Hold
-        }
-    }
-
-    pub fn get_ema_signal(&self, price: f64) -> TradeSignal {
-        let current_ema = self.price_ema.get_ema();
-
-        if current_ema < price {
-            TradeSignal::Buy
-        } else if current_ema > price {
-            TradeSignal::Sell
-        } else {
-            TradeSignal::Hold
-        }
-    }
-}
-
 #[derive(Debug)]
 pub struct TradingBot {
-    long_trading: TradingIndicator,
+    strategy: Box<dyn Strategy>,
+    transform: Option<Box<dyn CandleTransform>>,
+    levels: Option<SupportResistance>,
     atr: Atr,
     can_trade: bool,
+    can_short: bool,
+    candle_count: usize,
     pub candle: Candlestick,
     pub initialise: bool,
 }
 
 impl TradingBot {
-    pub fn new() -> Self {
-        let long_trading = TradingIndicator::new();
+    pub fn new(config: &SymbolConfig) -> Self {
+        let mut strategy = config.strategy.build();
+
+        if let Some(regime_filter) = &config.regime_filter {
+            strategy = Box::new(RegimeFilter::new(strategy, regime_filter));
+        }
+
+        if let Some(pattern_filter) = &config.pattern_filter {
+            strategy = Box::new(PatternFilter::new(strategy, pattern_filter));
+        }
+
         let atr = Atr::new(14);
 
         TradingBot {
-            long_trading,
+            strategy,
+            transform: config.transform.map(|transform| transform.build()),
+            levels: config.levels.as_ref().map(SupportResistance::new),
             atr,
             can_trade: true,
+            can_short: true,
+            candle_count: 0,
             candle: Candlestick {
                 start: 0,
                 low: 0.0,
@@ -104,27 +64,103 @@ impl TradingBot {
         }
     }
 
+    // The stop atr always uses the raw candles, only the strategy sees
+    // transformed ones.
     pub fn one_minute_update(&mut self, candle: Candlestick) {
-        self.long_trading.update(candle.close);
-        self.atr.update(candle.high, candle.low, candle.close);
+        self.atr.on_candle(&candle);
+        if let Some(levels) = self.levels.as_mut() {
+            levels.on_candle(&candle);
+        }
+
+        match self.transform.as_mut() {
+            Some(transform) => {
+                for transformed in transform.on_candle(&candle) {
+                    self.strategy.on_candle(&transformed);
+                    self.candle_count += 1;
+                }
+            }
+            None => {
+                self.strategy.on_candle(&candle);
+                self.candle_count += 1;
+            }
+        }
+    }
+
+    // Clears the indicators so the next snapshot replays history into them,
+    // the trade flags are kept as they follow the open position.
+    pub fn reset(&mut self) {
+        self.strategy.reset();
+        if let Some(transform) = self.transform.as_mut() {
+            transform.reset();
+        }
+        if let Some(levels) = self.levels.as_mut() {
+            levels.reset();
+        }
+        self.atr.reset();
+        self.candle_count = 0;
+        self.initialise = false;
     }
 
-    pub fn get_signal(&mut self, price: f64) -> TradeSignal {
-        let ema_signal = self.long_trading.get_ema_signal(price);
-        let macd_signal = self.long_trading.get_macd_signal();
-
-        if ema_signal == TradeSignal::Buy && macd_signal == TradeSignal::Buy {
-            TradeSignal::Buy
-        } else if ema_signal == TradeSignal::Sell && macd_signal == TradeSignal::Sell {
-            self.can_trade = true;
-            TradeSignal::Sell
-        } else {
-            TradeSignal::Hold
+    pub fn get_signal(&mut self) -> TradeSignal {
+        // Don't act on indicators that haven't seen enough candles yet.
+        if self.candle_count < self.strategy.warmup_period() {
+            return TradeSignal::Hold;
         }
+
+        let signal = match self.strategy.get_signal() {
+            // Skip entries with resistance too close overhead.
+            TradeSignal::Buy if !self.allows_entry() => TradeSignal::Hold,
+            signal => signal,
+        };
+
+        match signal {
+            TradeSignal::Buy => self.can_short = true,
+            TradeSignal::Sell => self.can_trade = true,
+            TradeSignal::Hold => (),
+        }
+
+        signal
+    }
+
+    fn allows_entry(&self) -> bool {
+        match (&self.levels, self.atr.get_value()) {
+            (Some(levels), Some(atr)) => levels.allows_entry(atr),
+            _ => true,
+        }
+    }
+
+    #[inline]
+    pub fn uses_pivots(&self) -> bool {
+        self.levels
+            .as_ref()
+            .is_some_and(|levels| levels.uses_pivots())
+    }
+
+    pub fn set_previous_day(&mut self, day: &Candlestick) {
+        if let Some(levels) = self.levels.as_mut() {
+            levels.set_previous_day(day);
+        }
+    }
+
+    pub fn get_warmup_period(&self) -> usize {
+        self.strategy.warmup_period()
+    }
+
+    // The strategy's own stop comes first, then one under the nearest support.
+    pub fn get_stop(&self, candle: &Candlestick) -> Option<f64> {
+        self.strategy.get_stop(candle).or_else(|| {
+            let levels = self.levels.as_ref()?;
+            levels.get_stop(candle.close, self.atr.get_value()?)
+        })
     }
 
     pub fn get_atr_value(&self) -> Option<f64> {
-        self.atr.get_atr().map(|atr| atr * ATR_MODIFIER)
+        self.atr.get_value().map(|atr| atr * ATR_MODIFIER)
+    }
+
+    // Without ATR_MODIFIER, for callers that scale it themselves.
+    pub fn get_raw_atr_value(&self) -> Option<f64> {
+        self.atr.get_value()
     }
 
     pub fn get_can_trade(&self) -> bool {
@@ -134,10 +170,23 @@ impl TradingBot {
     pub fn set_can_trade(&mut self, can_trade: bool) {
         self.can_trade = can_trade
     }
+
+    // Sell side entries are re-armed by a buy signal, as long entries are by a sell.
+    pub fn get_can_short(&self) -> bool {
+        self.can_short
+    }
+
+    pub fn set_can_short(&mut self, can_short: bool) {
+        self.can_short = can_short
+    }
 }
 
 pub struct IndicatorResult {
+    // The candle that just closed.
+    pub candle: Candlestick,
     pub signal: TradeSignal,
     pub atr: Option<f64>,
     pub high: f64,
+    pub low: f64,
+    pub stop: Option<f64>,
 }
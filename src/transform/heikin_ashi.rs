@@ -0,0 +1,44 @@
+use smallvec::{smallvec, SmallVec};
+
+use crate::model::event::Candlestick;
+
+use super::CandleTransform;
+
+// Averaged candles, each open is the midpoint of the previous heikin ashi
+// candle's body which smooths out single candle noise.
+#[derive(Debug, Default)]
+pub struct HeikinAshi {
+    prev: Option<Candlestick>,
+}
+
+impl HeikinAshi {
+    pub fn new() -> Self {
+        HeikinAshi::default()
+    }
+}
+
+impl CandleTransform for HeikinAshi {
+    fn on_candle(&mut self, candle: &Candlestick) -> SmallVec<[Candlestick; 1]> {
+        let close = (candle.open + candle.high + candle.low + candle.close) / 4.0;
+        let open = match self.prev {
+            Some(prev) => (prev.open + prev.close) / 2.0,
+            None => (candle.open + candle.close) / 2.0,
+        };
+
+        let heikin_ashi = Candlestick {
+            start: candle.start,
+            low: candle.low.min(open).min(close),
+            high: candle.high.max(open).max(close),
+            open,
+            close,
+            volume: candle.volume,
+        };
+
+        self.prev = Some(heikin_ashi);
+        smallvec![heikin_ashi]
+    }
+
+    fn reset(&mut self) {
+        self.prev = None;
+    }
+}
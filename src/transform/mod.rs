@@ -0,0 +1,64 @@
+use std::fmt;
+
+use serde::Deserialize;
+use smallvec::SmallVec;
+
+use crate::model::event::Candlestick;
+
+use self::{heikin_ashi::HeikinAshi, renko::Renko};
+
+pub mod heikin_ashi;
+pub mod renko;
+
+const RENKO_ATR_PERIOD: usize = 14;
+
+// Turns the closed candle stream into a different candle stream before it
+// reaches the strategy. A transform can emit no candles for an input, or several.
+pub trait CandleTransform: fmt::Debug + Send {
+    fn on_candle(&mut self, candle: &Candlestick) -> SmallVec<[Candlestick; 1]>;
+
+    fn reset(&mut self);
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransformConfig {
+    HeikinAshi,
+    // Bricks are `multiplier` times the atr of the raw candles.
+    Renko {
+        #[serde(default = "default_atr_period")]
+        atr_period: usize,
+        #[serde(default = "default_multiplier")]
+        multiplier: f64,
+    },
+}
+
+fn default_atr_period() -> usize {
+    RENKO_ATR_PERIOD
+}
+
+fn default_multiplier() -> f64 {
+    1.0
+}
+
+impl TransformConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            TransformConfig::Renko { multiplier, .. } if multiplier <= 0.0 => Err(format!(
+                "renko multiplier must be positive, got {}",
+                multiplier
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn build(&self) -> Box<dyn CandleTransform> {
+        match *self {
+            TransformConfig::HeikinAshi => Box::new(HeikinAshi::new()),
+            TransformConfig::Renko {
+                atr_period,
+                multiplier,
+            } => Box::new(Renko::new(atr_period, multiplier)),
+        }
+    }
+}
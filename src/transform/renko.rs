@@ -0,0 +1,153 @@
+use smallvec::SmallVec;
+
+use crate::{
+    indicators::{atr::Atr, Indicator},
+    model::event::Candlestick,
+};
+
+use super::CandleTransform;
+
+// Fixed size bricks that only form once price has moved a full brick past
+// the last one. The brick size follows the atr of the raw candles.
+#[derive(Debug)]
+pub struct Renko {
+    atr: Atr,
+    multiplier: f64,
+    // Open and close of the last brick, the first one is flat.
+    brick_open: f64,
+    brick_close: f64,
+    started: bool,
+}
+
+impl Renko {
+    pub fn new(atr_period: usize, multiplier: f64) -> Self {
+        Renko {
+            atr: Atr::new(atr_period),
+            multiplier,
+            brick_open: 0.0,
+            brick_close: 0.0,
+            started: false,
+        }
+    }
+
+    fn push_brick(
+        &mut self,
+        bricks: &mut SmallVec<[Candlestick; 1]>,
+        candle: &Candlestick,
+        open: f64,
+        close: f64,
+    ) {
+        // The candle's volume goes on the first brick it forms.
+        let volume = if bricks.is_empty() {
+            candle.volume
+        } else {
+            0.0
+        };
+
+        bricks.push(Candlestick {
+            start: candle.start,
+            low: open.min(close),
+            high: open.max(close),
+            open,
+            close,
+            volume,
+        });
+
+        self.brick_open = open;
+        self.brick_close = close;
+    }
+}
+
+impl CandleTransform for Renko {
+    fn on_candle(&mut self, candle: &Candlestick) -> SmallVec<[Candlestick; 1]> {
+        let mut bricks = SmallVec::new();
+
+        // A brick size that isn't positive would never finish a brick.
+        let size = match self.atr.on_candle(candle).map(|atr| atr * self.multiplier) {
+            Some(size) if size > 0.0 => size,
+            _ => return bricks,
+        };
+
+        if !self.started {
+            self.brick_open = candle.close;
+            self.brick_close = candle.close;
+            self.started = true;
+            return bricks;
+        }
+
+        // New bricks start from the far side of the last brick's body, so a
+        // reversal needs twice the move of a continuation.
+        let top = self.brick_open.max(self.brick_close);
+        let bottom = self.brick_open.min(self.brick_close);
+
+        if candle.close >= top + size {
+            let mut open = top;
+            while candle.close >= open + size {
+                self.push_brick(&mut bricks, candle, open, open + size);
+                open += size;
+            }
+        } else if candle.close <= bottom - size {
+            let mut open = bottom;
+            while candle.close <= open - size {
+                self.push_brick(&mut bricks, candle, open, open - size);
+                open -= size;
+            }
+        }
+
+        bricks
+    }
+
+    fn reset(&mut self) {
+        self.atr.reset();
+        self.brick_open = 0.0;
+        self.brick_close = 0.0;
+        self.started = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::testing::ohlcv;
+
+    // With a one candle atr each brick is the candle's true range.
+    const CANDLES: [(f64, f64, f64, f64); 4] = [
+        (10.0, 10.5, 9.5, 10.0),
+        (10.0, 12.0, 10.0, 12.0),
+        (12.0, 12.0, 8.0, 8.0),
+        (8.0, 8.0, 6.0, 6.0),
+    ];
+
+    fn feed(renko: &mut Renko) -> Vec<SmallVec<[Candlestick; 1]>> {
+        CANDLES
+            .iter()
+            .map(|&(open, high, low, close)| renko.on_candle(&ohlcv(open, high, low, close, 1.0)))
+            .collect()
+    }
+
+    #[test]
+    fn forms_bricks() {
+        let bricks = feed(&mut Renko::new(1, 1.0));
+
+        assert!(bricks[0].is_empty());
+
+        assert_eq!(bricks[1].len(), 1);
+        assert_eq!((bricks[1][0].open, bricks[1][0].close), (10.0, 12.0));
+
+        // The reversal needs a full brick past the bottom of the last one.
+        assert!(bricks[2].is_empty());
+
+        assert_eq!(bricks[3].len(), 2);
+        assert_eq!((bricks[3][0].open, bricks[3][0].close), (10.0, 8.0));
+        assert_eq!((bricks[3][1].open, bricks[3][1].close), (8.0, 6.0));
+        assert_eq!((bricks[3][0].volume, bricks[3][1].volume), (1.0, 0.0));
+    }
+
+    #[test]
+    fn non_positive_multiplier_forms_no_bricks() {
+        for multiplier in [0.0, -1.0] {
+            let bricks = feed(&mut Renko::new(1, multiplier));
+            assert!(bricks.iter().all(|bricks| bricks.is_empty()));
+        }
+    }
+}
@@ -6,26 +6,46 @@ use sha2::Sha256;
 
 type HmacSha256 = Hmac<Sha256>;
 
-pub fn subscribe(
-    ws_stream: &mut tungstenite::WebSocket<
-        tungstenite::stream::Stream<
-            std::net::TcpStream,
-            native_tls::TlsStream<std::net::TcpStream>,
-        >,
-    >,
-    market: &str,
+pub type WsStream = tungstenite::WebSocket<
+    tungstenite::stream::Stream<std::net::TcpStream, native_tls::TlsStream<std::net::TcpStream>>,
+>;
+
+// Stops read_message blocking forever so the heartbeat watchdog gets a chance to run.
+pub fn set_read_timeout(ws_stream: &WsStream, timeout: std::time::Duration) {
+    let tcp_stream = match ws_stream.get_ref() {
+        tungstenite::stream::Stream::Plain(stream) => stream,
+        tungstenite::stream::Stream::Tls(stream) => stream.get_ref(),
+    };
+
+    if let Err(e) = tcp_stream.set_read_timeout(Some(timeout)) {
+        println!("Failed to set socket read timeout: {}", e);
+    }
+}
+
+pub fn subscribe(ws_stream: &mut WsStream, market: &str, event: &str) {
+    subscribe_channels(
+        ws_stream,
+        &[market.to_string()],
+        &["heartbeats", "candles", "level2", "ticker"],
+        event,
+    );
+}
+
+pub fn subscribe_channels(
+    ws_stream: &mut WsStream,
+    markets: &[String],
+    channels: &[&str],
     event: &str,
 ) {
-    let channels = vec!["heartbeats", "candles"];
     for channel in channels.iter() {
         let timestamp = format!("{}", chrono::Utc::now().timestamp());
-        let msg_to_sign = format!("{}{}{}", timestamp, channel, market);
+        let msg_to_sign = format!("{}{}{}", timestamp, channel, markets.join(","));
         let signature = sign_message(&msg_to_sign);
         let api_key = std::env::var("API_KEY").expect("API_KEY not found in environment");
 
         let subscribe_msg = json!({
             "type": event.to_string(),
-            "product_ids": [market],
+            "product_ids": markets,
             "channel": channel,
             "api_key": api_key,
             "timestamp": timestamp,